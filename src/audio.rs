@@ -0,0 +1,88 @@
+//! Short synthesized sound effects played through the Web Audio API. Every
+//! sound is built on the fly from an oscillator or a noise buffer, so the
+//! crate ships no audio assets.
+
+use wasm_bindgen::JsValue;
+use web_sys::{AudioBuffer, AudioContext, GainNode, OscillatorType};
+
+use crate::SoundCue;
+
+/// Seconds a shot's beep lasts.
+pub const SHOOT_DURATION: f64 = 0.08;
+
+/// Seconds an explosion's noise burst lasts.
+pub const EXPLOSION_DURATION: f64 = 0.3;
+
+/// Pitch the shot's beep sweeps down from and to, in hertz.
+const SHOOT_PITCH: (f32, f32) = (880.0, 220.0);
+
+/// Peak gain of each effect, kept low so overlapping sounds don't clip.
+const SHOOT_VOLUME: f32 = 0.1;
+const EXPLOSION_VOLUME: f32 = 0.25;
+
+/// Gain an envelope fades to. Exponential ramps cannot reach zero.
+const SILENCE: f32 = 0.001;
+
+/// A live audio context and the noise every explosion replays.
+pub struct Audio {
+    context: AudioContext,
+    noise: AudioBuffer,
+}
+
+impl Audio {
+    /// Opens an audio context, or `None` where Web Audio is unavailable.
+    pub fn new() -> Option<Audio> {
+        let context = AudioContext::new().ok()?;
+        let rate = context.sample_rate();
+        let length = (rate as f64 * EXPLOSION_DURATION) as u32;
+        let noise = context.create_buffer(1, length, rate).ok()?;
+        let samples: Vec<f32> = (0..length).map(|_| rand::random::<f32>() * 2.0 - 1.0).collect();
+        noise.copy_to_channel(&samples, 0).ok()?;
+        Some(Audio { context, noise })
+    }
+
+    /// Plays the effect for `cue`. Failures are dropped; a missed sound is
+    /// not worth interrupting the game for.
+    pub fn play(&self, cue: SoundCue) {
+        // Contexts made before the page saw a user gesture start suspended.
+        let _ = self.context.resume();
+        let _ = match cue {
+            SoundCue::Shoot => self.beep(),
+            SoundCue::Explosion => self.burst(),
+        };
+    }
+
+    /// A square wave sweeping down in pitch.
+    fn beep(&self) -> Result<(), JsValue> {
+        let now = self.context.current_time();
+        let oscillator = self.context.create_oscillator()?;
+        oscillator.set_type(OscillatorType::Square);
+        let frequency = oscillator.frequency();
+        frequency.set_value_at_time(SHOOT_PITCH.0, now)?;
+        frequency.exponential_ramp_to_value_at_time(SHOOT_PITCH.1, now + SHOOT_DURATION)?;
+        let envelope = self.envelope(SHOOT_VOLUME, SHOOT_DURATION)?;
+        oscillator.connect_with_audio_node(&envelope)?;
+        oscillator.start()?;
+        oscillator.stop_with_when(now + SHOOT_DURATION)
+    }
+
+    /// White noise fading out.
+    fn burst(&self) -> Result<(), JsValue> {
+        let source = self.context.create_buffer_source()?;
+        source.set_buffer(Some(&self.noise));
+        let envelope = self.envelope(EXPLOSION_VOLUME, EXPLOSION_DURATION)?;
+        source.connect_with_audio_node(&envelope)?;
+        source.start()
+    }
+
+    /// A gain node wired to the speakers that starts at `volume` and fades
+    /// to silence over `duration` seconds.
+    fn envelope(&self, volume: f32, duration: f64) -> Result<GainNode, JsValue> {
+        let now = self.context.current_time();
+        let gain = self.context.create_gain()?;
+        gain.gain().set_value_at_time(volume, now)?;
+        gain.gain().exponential_ramp_to_value_at_time(SILENCE, now + duration)?;
+        gain.connect_with_audio_node(&self.context.destination())?;
+        Ok(gain)
+    }
+}
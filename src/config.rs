@@ -0,0 +1,443 @@
+use serde::{Deserialize, Serialize};
+
+use crate::events::EventOverflow;
+
+/// Default top speed of the ship, in pixels per second.
+pub const MAX_SHIP_SPEED: f64 = 600.0;
+
+/// Default share of the ship's velocity lost every sixtieth of a second, so
+/// it coasts to a stop once thrust lets up.
+pub const SHIP_DRAG: f64 = 0.01;
+
+/// Tuning values shared by the simulation. Hosts adjust these through the
+/// setters on `Game`, or swap the whole set at once as JSON; the defaults
+/// reproduce the classic feel.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    /// Fastest an asteroid may travel, in pixels per second. Anything faster
+    /// is scaled back down with its direction preserved.
+    pub max_asteroid_speed: f64,
+    /// Where the ship reappears after losing a life or continuing.
+    pub respawn_strategy: RespawnStrategy,
+    /// Lives granted when the player continues after a game over.
+    pub continue_lives: u32,
+    /// Fraction of the score kept on each continue.
+    pub continue_score_factor: f64,
+    /// Most player bullets allowed in flight at once.
+    pub max_bullets: usize,
+    /// Bullets fired by each shot, fanned evenly across `spread_angle`.
+    pub pellets: u32,
+    /// Radians between the outermost pellets of a shot.
+    pub spread_angle: f64,
+    /// Extra speed each wave's asteroids start with over the wave before,
+    /// as a fraction of the first wave's speed.
+    pub wave_speedup: f64,
+    /// How far shots bend toward the nearest asteroid in the aim cone, from
+    /// 0 (not at all) to 1 (straight at it).
+    pub aim_assist: f64,
+    /// Half-angle, in radians, of the cone ahead of the nose that aim assist
+    /// looks for targets in.
+    pub aim_assist_cone: f64,
+    /// Speed of split fragments relative to the parent asteroid.
+    pub split_speed_multiplier: f64,
+    /// Blend between fragments inheriting the parent's velocity (0) and
+    /// flying off with fresh random velocities (1).
+    pub split_randomness: f64,
+    /// Acceleration while thrusting, in pixels per second squared.
+    pub thrust_power: f64,
+    /// Share of a full tank burned per second of thrust. Zero gives
+    /// unlimited fuel.
+    pub fuel_burn_rate: f64,
+    /// Share of a full tank each shot costs.
+    pub fuel_per_shot: f64,
+    /// Share of a full tank refilled per second without thrust.
+    pub fuel_regen_rate: f64,
+    /// Radians between the nose and the direction thrust pushes, for
+    /// lopsided or damaged engines. Positive values drift clockwise.
+    pub thrust_offset: f64,
+    /// Fraction of the ship's velocity lost every sixtieth of a second.
+    pub ship_damping: f64,
+    /// Radians per second the ship turns while rotation input is held.
+    pub rotation_speed: f64,
+    /// Fastest the ship may travel, in pixels per second. Written as `null`
+    /// in JSON when unlimited.
+    #[serde(with = "unbounded")]
+    pub max_ship_speed: f64,
+    /// Acceleration applied to every bullet, as `(x, y)` in pixels per
+    /// second squared, so shots arc. Zero keeps them flying straight.
+    pub bullet_gravity: (f64, f64),
+    /// Whether player bullets shoot down enemy bullets they touch.
+    pub bullet_interception: bool,
+    /// Seconds a fresh bullet flies before it can hit anything, so a shot
+    /// fired into a rock sitting on the muzzle is not spent on the spot.
+    pub bullet_arming_time: f64,
+    /// How far past an edge an entity may drift before it is moved to the
+    /// opposite side. Ghost copies cover the seam in the meantime.
+    pub wrap_buffer: f64,
+    /// What the ship does at the field's edges. Asteroids, bullets and
+    /// saucers always wrap.
+    pub wrap_mode: WrapMode,
+    /// Whether asteroids bounce off one another, trading momentum by mass.
+    pub asteroid_collisions: bool,
+    /// Whether fast asteroids draw a faint motion trail behind them.
+    pub asteroid_trails: bool,
+    /// Speed, in pixels per second, an asteroid must exceed to leave a
+    /// trail.
+    pub trail_speed_threshold: f64,
+    /// Largest angle, in radians, an edge-spawned asteroid's heading may
+    /// stray from pointing straight into the field.
+    pub edge_spawn_spread: f64,
+    /// Speed of edge-spawned asteroids as they enter, in pixels per second.
+    pub edge_entry_speed: f64,
+    /// How far inside the edge those asteroids first appear.
+    pub edge_spawn_offset: f64,
+    /// Whether turn input spins the ship up gradually and lets it coast
+    /// down, rather than turning it only while the input is held.
+    pub turning_inertia: bool,
+    /// With inertia on, the share of `rotation_speed` each second of turn
+    /// input adds to the ship's spin.
+    pub turn_ramp: f64,
+    /// With inertia on, the fraction of the ship's spin lost every sixtieth
+    /// of a second.
+    pub turn_damping: f64,
+    /// Seconds between asteroids appearing as a wave starts. Zero brings the
+    /// whole wave in at once.
+    pub wave_intro_interval: f64,
+    /// Most undrained events kept, so a host that never drains cannot grow
+    /// the log without bound.
+    pub event_log_cap: usize,
+    /// Which event is discarded when the log is full.
+    pub event_overflow: EventOverflow,
+    /// Whether asteroids that pass close by the ship without touching it
+    /// earn a bonus.
+    pub near_miss_scoring: bool,
+    /// How far beyond touching distance still counts as a near miss.
+    pub near_miss_band: f64,
+    /// Half-angle, in radians, of a cone ahead of the ship where new
+    /// asteroids may not appear. Zero leaves the line of fire unprotected.
+    pub spawn_safe_cone: f64,
+    /// Whether asteroids can only be destroyed through their weak spot, the
+    /// side facing the ship. Hits anywhere else glance off.
+    pub weak_spot_mode: bool,
+    /// Half-width of the weak spot, in radians either side of the line to
+    /// the ship.
+    pub weak_spot_arc: f64,
+    /// Whether asteroid speed and numbers follow the player's accuracy and
+    /// deaths from wave to wave.
+    pub adaptive_difficulty: bool,
+    /// Whether flying saucers may appear.
+    pub ufos_enabled: bool,
+    /// Whether power-ups such as the magnet can be activated.
+    pub power_ups_enabled: bool,
+    /// Whether asteroids that hit the ship drain its health rather than
+    /// costing a life outright. A life goes only once health runs out.
+    pub collision_damage: bool,
+    /// Health the ship starts each life with when collision damage is on.
+    pub max_health: f64,
+    /// Damage per pixel of an asteroid's radius, so bigger rocks hurt more.
+    pub damage_per_size: f64,
+    /// Damage per pixel per second the asteroid and ship were closing at.
+    pub damage_per_speed: f64,
+    /// Chance, from 0 to 1, that a hyperspace jump destroys the ship.
+    pub hyperspace_risk: f64,
+    /// Whether particles are emitted, updated and drawn at all.
+    pub particles_enabled: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig {
+            max_asteroid_speed: 240.0,
+            respawn_strategy: RespawnStrategy::Center,
+            continue_lives: 3,
+            continue_score_factor: 0.5,
+            max_bullets: 16,
+            pellets: 1,
+            spread_angle: 0.0,
+            wave_speedup: 0.1,
+            aim_assist: 0.0,
+            aim_assist_cone: 0.3,
+            split_speed_multiplier: 1.25,
+            split_randomness: 0.0,
+            thrust_power: 1800.0,
+            fuel_burn_rate: 0.0,
+            fuel_per_shot: 0.0,
+            fuel_regen_rate: 0.1,
+            thrust_offset: 0.0,
+            ship_damping: SHIP_DRAG,
+            rotation_speed: 6.0,
+            max_ship_speed: MAX_SHIP_SPEED,
+            bullet_gravity: (0.0, 0.0),
+            bullet_interception: false,
+            bullet_arming_time: 0.02,
+            wrap_buffer: 10.0,
+            wrap_mode: WrapMode::Wrap,
+            asteroid_collisions: false,
+            asteroid_trails: false,
+            trail_speed_threshold: 120.0,
+            edge_spawn_spread: 0.5,
+            edge_entry_speed: 60.0,
+            edge_spawn_offset: 0.0,
+            turning_inertia: false,
+            turn_ramp: 15.0,
+            turn_damping: 0.15,
+            wave_intro_interval: 0.0,
+            event_log_cap: 256,
+            event_overflow: EventOverflow::DropOldest,
+            near_miss_scoring: false,
+            near_miss_band: 15.0,
+            spawn_safe_cone: 0.0,
+            weak_spot_mode: false,
+            weak_spot_arc: std::f64::consts::FRAC_PI_4,
+            adaptive_difficulty: false,
+            ufos_enabled: true,
+            power_ups_enabled: true,
+            collision_damage: false,
+            max_health: 100.0,
+            damage_per_size: 1.5,
+            damage_per_speed: 0.2,
+            hyperspace_risk: 0.0,
+            particles_enabled: true,
+        }
+    }
+}
+
+impl GameConfig {
+    /// Parses a config exported by `to_json`. Fields left out keep their
+    /// defaults; malformed JSON or out-of-range values are an error.
+    pub fn from_json(json: &str) -> Result<GameConfig, String> {
+        let config: GameConfig = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Checks every value against the same limits the individual setters
+    /// enforce.
+    pub fn validate(&self) -> Result<(), String> {
+        let non_negative = [
+            ("continue_score_factor", self.continue_score_factor),
+            ("split_speed_multiplier", self.split_speed_multiplier),
+            ("split_randomness", self.split_randomness),
+            ("wave_speedup", self.wave_speedup),
+            ("thrust_power", self.thrust_power),
+            ("fuel_burn_rate", self.fuel_burn_rate),
+            ("fuel_per_shot", self.fuel_per_shot),
+            ("fuel_regen_rate", self.fuel_regen_rate),
+            ("ship_damping", self.ship_damping),
+            ("rotation_speed", self.rotation_speed),
+            ("wrap_buffer", self.wrap_buffer),
+            ("bullet_arming_time", self.bullet_arming_time),
+            ("trail_speed_threshold", self.trail_speed_threshold),
+            ("edge_spawn_spread", self.edge_spawn_spread),
+            ("edge_entry_speed", self.edge_entry_speed),
+            ("edge_spawn_offset", self.edge_spawn_offset),
+            ("turn_ramp", self.turn_ramp),
+            ("turn_damping", self.turn_damping),
+            ("wave_intro_interval", self.wave_intro_interval),
+            ("weak_spot_arc", self.weak_spot_arc),
+            ("spawn_safe_cone", self.spawn_safe_cone),
+            ("near_miss_band", self.near_miss_band),
+            ("spread_angle", self.spread_angle),
+            ("aim_assist", self.aim_assist),
+            ("aim_assist_cone", self.aim_assist_cone),
+            ("damage_per_size", self.damage_per_size),
+            ("damage_per_speed", self.damage_per_speed),
+            ("hyperspace_risk", self.hyperspace_risk),
+        ];
+        for (name, value) in non_negative {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("{name} must be a non-negative number"));
+            }
+        }
+        if !(self.bullet_gravity.0.is_finite() && self.bullet_gravity.1.is_finite()) {
+            return Err("bullet_gravity must be a pair of numbers".to_string());
+        }
+        if !self.thrust_offset.is_finite() {
+            return Err("thrust_offset must be a number".to_string());
+        }
+        if !(self.max_asteroid_speed.is_finite() && self.max_asteroid_speed > 0.0) {
+            return Err("max_asteroid_speed must be positive".to_string());
+        }
+        if !(self.max_health.is_finite() && self.max_health > 0.0) {
+            return Err("max_health must be positive".to_string());
+        }
+        if self.max_ship_speed.is_nan() || self.max_ship_speed <= 0.0 {
+            return Err("max_ship_speed must be positive".to_string());
+        }
+        let fractions = [
+            ("continue_score_factor", self.continue_score_factor),
+            ("ship_damping", self.ship_damping),
+            ("turn_damping", self.turn_damping),
+            ("aim_assist", self.aim_assist),
+            ("hyperspace_risk", self.hyperspace_risk),
+            ("split_randomness", self.split_randomness),
+        ];
+        for (name, value) in fractions {
+            if value > 1.0 {
+                return Err(format!("{name} must be at most 1"));
+            }
+        }
+        if self.edge_spawn_spread >= std::f64::consts::FRAC_PI_2 {
+            return Err("edge_spawn_spread must be less than a right angle".to_string());
+        }
+        if self.pellets == 0 {
+            return Err("pellets must be at least 1".to_string());
+        }
+        if self.continue_lives == 0 {
+            return Err("continue_lives must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// How the spot the ship respawns at is chosen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RespawnStrategy {
+    /// The middle of the field, as in the arcade original.
+    #[default]
+    Center,
+    /// Wherever is farthest from every asteroid.
+    SafestPoint,
+    /// The last place the ship was with no asteroid close by.
+    LastSafePosition,
+}
+
+impl RespawnStrategy {
+    pub fn from_name(name: &str) -> Option<RespawnStrategy> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "center" | "centre" => Some(RespawnStrategy::Center),
+            "safest_point" | "safest" => Some(RespawnStrategy::SafestPoint),
+            "last_safe_position" | "last_safe" => Some(RespawnStrategy::LastSafePosition),
+            _ => None,
+        }
+    }
+}
+
+/// What the ship does when it reaches an edge of the field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WrapMode {
+    /// Leave by one edge and come back in at the opposite one.
+    #[default]
+    Wrap,
+    /// Stop at the edge, losing the velocity that pointed out of the field.
+    Clamp,
+    /// Rebound off the edge with that velocity reflected.
+    Bounce,
+}
+
+impl WrapMode {
+    pub fn from_name(name: &str) -> Option<WrapMode> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "wrap" => Some(WrapMode::Wrap),
+            "clamp" => Some(WrapMode::Clamp),
+            "bounce" => Some(WrapMode::Bounce),
+            _ => None,
+        }
+    }
+}
+
+/// Stores an optional upper limit, with infinity standing for "none", since
+/// JSON has no way to spell infinity.
+mod unbounded {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_finite() {
+            serializer.serialize_some(value)
+        } else {
+            serializer.serialize_none()
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::INFINITY))
+    }
+}
+
+/// Bundles of ship handling constants that are tuned together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeelProfile {
+    Classic,
+    Floaty,
+    Arcade,
+    Heavy,
+}
+
+impl FeelProfile {
+    pub fn from_name(name: &str) -> Option<FeelProfile> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "classic" => Some(FeelProfile::Classic),
+            "floaty" => Some(FeelProfile::Floaty),
+            "arcade" => Some(FeelProfile::Arcade),
+            "heavy" => Some(FeelProfile::Heavy),
+            _ => None,
+        }
+    }
+
+    /// Overwrites the ship handling values in `config`, leaving everything
+    /// else alone.
+    pub fn apply(self, config: &mut GameConfig) {
+        let (thrust, damping, rotation, max_speed) = match self {
+            FeelProfile::Classic => {
+                let classic = GameConfig::default();
+                (
+                    classic.thrust_power,
+                    classic.ship_damping,
+                    classic.rotation_speed,
+                    classic.max_ship_speed,
+                )
+            }
+            FeelProfile::Floaty => (720.0, 0.002, 4.8, 600.0),
+            FeelProfile::Arcade => (2160.0, 0.05, 7.2, 480.0),
+            FeelProfile::Heavy => (900.0, 0.03, 3.6, 360.0),
+        };
+        config.thrust_power = thrust;
+        config.ship_damping = damping;
+        config.rotation_speed = rotation;
+        config.max_ship_speed = max_speed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feel_profiles() {
+        let mut heavy = GameConfig::default();
+        FeelProfile::from_name("Heavy").unwrap().apply(&mut heavy);
+        let mut floaty = GameConfig::default();
+        FeelProfile::from_name("floaty").unwrap().apply(&mut floaty);
+
+        assert!(heavy.ship_damping > floaty.ship_damping);
+        assert_eq!(heavy.max_asteroid_speed, floaty.max_asteroid_speed);
+        assert_eq!(FeelProfile::from_name("wobbly"), None);
+    }
+
+    #[test]
+    fn test_config_json_round_trip() {
+        let config = GameConfig::default();
+        assert_eq!(GameConfig::from_json(&config.to_json()), Ok(config));
+
+        let mut tuned = GameConfig::default();
+        FeelProfile::Arcade.apply(&mut tuned);
+        tuned.asteroid_collisions = true;
+        assert_eq!(GameConfig::from_json(&tuned.to_json()), Ok(tuned));
+
+        let partial = GameConfig::from_json(r#"{"max_bullets": 4}"#).unwrap();
+        assert_eq!(partial.max_bullets, 4);
+        assert_eq!(partial.thrust_power, GameConfig::default().thrust_power);
+
+        assert!(GameConfig::from_json("{not json").is_err());
+        assert!(GameConfig::from_json(r#"{"ship_damping": 2.0}"#).is_err());
+        assert!(GameConfig::from_json(r#"{"max_asteroid_speed": -1.0}"#).is_err());
+        assert!(GameConfig::from_json(r#"{"edge_spawn_spread": 1.6}"#).is_err());
+    }
+}
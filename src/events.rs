@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Something noteworthy that happened during the simulation. Hosts drain
+/// these each frame to trigger sounds and effects.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum GameEvent {
+    /// A bullet left the ship's nose at `position`, travelling along `angle`.
+    BulletFired { position: (f64, f64), angle: f64 },
+    /// Every asteroid in `wave` was destroyed, earning the listed bonuses.
+    WaveCleared {
+        wave: u32,
+        time_bonus: u32,
+        accuracy_bonus: u32,
+    },
+    /// The canvas went away or stopped drawing, and the game paused itself.
+    ContextLost,
+}
+
+/// What to do with a new event when the log is already at its cap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventOverflow {
+    /// Discard the oldest event to make room, keeping the latest history.
+    #[default]
+    DropOldest,
+    /// Discard the new event, keeping what the host has not read yet.
+    DropNewest,
+}
+
+impl EventOverflow {
+    pub fn from_name(name: &str) -> Option<EventOverflow> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "drop_oldest" | "oldest" => Some(EventOverflow::DropOldest),
+            "drop_newest" | "newest" => Some(EventOverflow::DropNewest),
+            _ => None,
+        }
+    }
+}
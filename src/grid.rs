@@ -0,0 +1,88 @@
+//! A uniform grid over the wrapping play field, for finding the entities
+//! near a point without testing every one of them.
+
+/// Widest a grid cell is allowed to be, in pixels.
+pub const CELL_SIZE: f64 = 64.0;
+
+/// Extra reach given to every inserted circle so that points right on a
+/// cell edge are never missed to rounding.
+const MARGIN: f64 = 1.0;
+
+/// Entity indices bucketed by the cells their circles overlap. Cells wrap
+/// at the field's edges the same way entities do, so a circle straddling a
+/// seam is filed on both sides.
+pub struct SpatialGrid {
+    columns: usize,
+    rows: usize,
+    cell_width: f64,
+    cell_height: f64,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    /// An empty grid over a `width` by `height` field, split into cells no
+    /// larger than `CELL_SIZE` that tile it exactly.
+    pub fn new(width: f64, height: f64) -> SpatialGrid {
+        let cells_across = |span: f64| ((span / CELL_SIZE).ceil() as usize).max(1);
+        let (columns, rows) = (cells_across(width), cells_across(height));
+        SpatialGrid {
+            columns,
+            rows,
+            cell_width: width / columns as f64,
+            cell_height: height / rows as f64,
+            cells: vec![Vec::new(); columns * rows],
+        }
+    }
+
+    /// Files `index` under every cell the circle at `(x, y)` overlaps.
+    pub fn insert(&mut self, index: usize, (x, y): (f64, f64), radius: f64) {
+        let reach = radius + MARGIN;
+        let columns = cell_range(x - reach, x + reach, self.cell_width, self.columns);
+        let rows = cell_range(y - reach, y + reach, self.cell_height, self.rows);
+        for row in rows {
+            for column in columns.clone() {
+                self.cells[row * self.columns + column].push(index);
+            }
+        }
+    }
+
+    /// Indices filed under the cell holding `(x, y)`. Every circle that
+    /// contains the point is among them, along with some that only come
+    /// close.
+    pub fn near(&self, (x, y): (f64, f64)) -> &[usize] {
+        let column = cell_index(x, self.cell_width, self.columns);
+        let row = cell_index(y, self.cell_height, self.rows);
+        &self.cells[row * self.columns + column]
+    }
+}
+
+/// The cell along one axis holding `position`, wrapped into the field.
+fn cell_index(position: f64, cell: f64, count: usize) -> usize {
+    ((position / cell).floor() as i64).rem_euclid(count as i64) as usize
+}
+
+/// The cells along one axis covered from `low` to `high`, wrapping around
+/// and never listing a cell twice.
+fn cell_range(low: f64, high: f64, cell: f64, count: usize) -> impl Iterator<Item = usize> + Clone {
+    let first = (low / cell).floor() as i64;
+    let span = ((high / cell).floor() as i64 - first + 1).min(count as i64);
+    (first..first + span).map(move |index| index.rem_euclid(count as i64) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circles_are_filed_across_seams() {
+        let mut grid = SpatialGrid::new(500.0, 300.0);
+        grid.insert(0, (250.0, 150.0), 10.0);
+        grid.insert(1, (495.0, 5.0), 20.0);
+        grid.insert(2, (100.0, 100.0), 2000.0);
+
+        assert_eq!(grid.near((250.0, 150.0)), [0, 2]);
+        assert_eq!(grid.near((10.0, 295.0)), [1, 2]);
+        assert_eq!(grid.near((-3.0, 301.0)), [1, 2]);
+        assert_eq!(grid.near((400.0, 40.0)), [2]);
+    }
+}
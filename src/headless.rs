@@ -0,0 +1,158 @@
+//! The simulation without a browser: no canvas, no DOM and no JS interop.
+//! Native test runners and servers drive it step by step and read the state
+//! back through plain getters.
+
+use crate::{Bounds, HeldInput, World};
+
+/// A game world driven one frame at a time, outside the browser.
+pub struct Simulation {
+    world: World,
+}
+
+impl Simulation {
+    pub fn new(width: f64, height: f64) -> Simulation {
+        Simulation {
+            world: World::new(Bounds::new(width, height)),
+        }
+    }
+
+    /// A simulation whose random choices all follow from `seed`.
+    pub fn seeded(width: f64, height: f64, seed: u64) -> Simulation {
+        Simulation {
+            world: World::seeded(Bounds::new(width, height), seed),
+        }
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    pub fn update(&mut self, dt: f64) {
+        self.world.update(dt);
+    }
+
+    /// Starts a new game, keeping the configuration.
+    pub fn restart(&mut self) {
+        self.world.restart();
+    }
+
+    /// Advances `dt` seconds at a time until `predicate` holds, giving up
+    /// after `max_steps`. Returns whether the condition was reached.
+    pub fn run_until(
+        &mut self,
+        dt: f64,
+        max_steps: u32,
+        predicate: impl Fn(&Simulation) -> bool,
+    ) -> bool {
+        for _ in 0..max_steps {
+            if predicate(self) {
+                return true;
+            }
+            self.update(dt);
+        }
+        predicate(self)
+    }
+
+    /// Applies the inputs packed into a byte, then advances `dt` seconds:
+    /// bit 0 turns left, bit 1 right, bit 2 thrusts, bit 3 shoots and bit 4
+    /// jumps to hyperspace. Paired with `seeded`, a byte stream replays the
+    /// same game every time, which suits fuzzers and property tests.
+    pub fn fuzz_step(&mut self, input: u8, dt: f64) {
+        self.world.fuzz_step(input, dt);
+    }
+
+    /// Sets which controls are held; each update acts on them until they
+    /// change.
+    pub fn set_input(&mut self, thrust: bool, left: bool, right: bool, shoot: bool) {
+        self.world.input = HeldInput {
+            thrust,
+            left,
+            right,
+            shoot,
+        };
+    }
+
+    pub fn hyperspace(&mut self) {
+        self.world.hyperspace();
+    }
+
+    pub fn shoot(&mut self) {
+        self.world.shoot();
+    }
+
+    pub fn thrust(&mut self) {
+        self.world.thrust();
+    }
+
+    pub fn rotate_left(&mut self) {
+        self.world.turn(-1.0);
+    }
+
+    pub fn rotate_right(&mut self) {
+        self.world.turn(1.0);
+    }
+
+    pub fn score(&self) -> u32 {
+        self.world.score
+    }
+
+    pub fn combo(&self) -> u32 {
+        self.world.combo
+    }
+
+    pub fn wave(&self) -> u32 {
+        self.world.wave
+    }
+
+    pub fn asteroid_count(&self) -> usize {
+        self.world.asteroids.len()
+    }
+
+    pub fn bullet_count(&self) -> usize {
+        self.world.bullets.len()
+    }
+
+    pub fn player_position(&self) -> (f64, f64) {
+        (self.world.player.x, self.world.player.y)
+    }
+
+    pub fn player_angle(&self) -> f64 {
+        self.world.player.angle
+    }
+
+    pub fn player_speed(&self) -> f64 {
+        self.world.player.speed()
+    }
+
+    /// Lists every entity with a non-finite position or velocity.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        self.world.validate()
+    }
+
+    /// Returns and clears pending events as a JSON array.
+    pub fn drain_events(&mut self) -> String {
+        let events: Vec<_> = self.world.events.drain(..).collect();
+        serde_json::to_string(&events).unwrap_or_else(|_| "[]".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_simulation() {
+        let mut sim = Simulation::new(800.0, 600.0);
+        assert_eq!(sim.player_position(), (400.0, 300.0));
+        assert_eq!(sim.asteroid_count(), 5);
+
+        sim.thrust();
+        sim.shoot();
+        assert_eq!(sim.bullet_count(), 1);
+        assert!(sim.drain_events().contains("BulletFired"));
+
+        sim.update(1.0 / 60.0);
+        let (_, y) = sim.player_position();
+        assert!(y < 300.0);
+
+        assert!(sim.run_until(1.0 / 60.0, 100, |sim| sim.bullet_count() == 0));
+        assert!(!sim.run_until(1.0 / 60.0, 3, |sim| sim.wave() > 5));
+    }
+}
@@ -0,0 +1,173 @@
+//! Hand-authored levels written in a compact line-based text format:
+//!
+//! ```text
+//! # comments run to the end of the line
+//! player 400 300 0.5          # x y [heading]
+//! asteroid 100 120 large      # x y size [vx vy]
+//! asteroid 600 80 15 30 -60
+//! wave 2 1 0 edges            # large medium small [edges]
+//! ```
+//!
+//! Entries are split by newlines or `;`. Sizes are `large`, `medium`,
+//! `small` or a radius in pixels, and velocities are in pixels per second.
+//! The placed asteroids make up the opening wave; `wave` lines script the
+//! waves that follow it.
+
+use crate::wave::{WaveSpec, LARGE_ASTEROID_SIZE, MEDIUM_ASTEROID_SIZE, SMALL_ASTEROID_SIZE};
+
+/// An asteroid placed by hand. Without a velocity it drifts randomly.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlacedAsteroid {
+    pub x: f64,
+    pub y: f64,
+    pub size: f64,
+    pub velocity: Option<(f64, f64)>,
+}
+
+/// Everything a level descriptor specifies.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Level {
+    /// Ship start as `(x, y, heading)`; the center of the field if absent.
+    pub player: Option<(f64, f64, f64)>,
+    pub asteroids: Vec<PlacedAsteroid>,
+    pub waves: Vec<WaveSpec>,
+}
+
+impl Level {
+    /// Parses a descriptor, naming the offending entry on error.
+    pub fn parse(descriptor: &str) -> Result<Level, String> {
+        let mut level = Level::default();
+        let entries = descriptor
+            .lines()
+            .enumerate()
+            .flat_map(|(line, text)| {
+                let text = text.split('#').next().unwrap_or("");
+                text.split(';').map(move |entry| (line + 1, entry))
+            });
+        for (line, entry) in entries {
+            let words: Vec<&str> = entry.split_whitespace().collect();
+            let Some((&keyword, args)) = words.split_first() else {
+                continue;
+            };
+            let context = |message: String| format!("line {line}: {message}");
+            match keyword {
+                "player" => {
+                    if level.player.is_some() {
+                        return Err(context("the player is placed twice".to_string()));
+                    }
+                    let numbers = numbers(args, 2..=3).map_err(context)?;
+                    let heading = numbers.get(2).copied().unwrap_or(0.0);
+                    level.player = Some((numbers[0], numbers[1], heading));
+                }
+                "asteroid" => {
+                    if !(args.len() == 3 || args.len() == 5) {
+                        return Err(context(format!(
+                            "asteroid takes x y size [vx vy], got {} values",
+                            args.len()
+                        )));
+                    }
+                    let position = numbers(&args[..2], 2..=2).map_err(context)?;
+                    let size = size(args[2]).map_err(context)?;
+                    let velocity = match numbers(&args[3..], 0..=2).map_err(context)?[..] {
+                        [vx, vy] => Some((vx, vy)),
+                        _ => None,
+                    };
+                    level.asteroids.push(PlacedAsteroid {
+                        x: position[0],
+                        y: position[1],
+                        size,
+                        velocity,
+                    });
+                }
+                "wave" => {
+                    let (counts, from_edges) = match args {
+                        [counts @ .., "edges"] => (counts, true),
+                        counts => (counts, false),
+                    };
+                    let [large, medium, small] = counts else {
+                        return Err(context("wave takes large medium small [edges]".to_string()));
+                    };
+                    let count = |word: &str| {
+                        word.parse::<u32>()
+                            .map_err(|_| context(format!("'{word}' is not a whole number")))
+                    };
+                    level.waves.push(WaveSpec {
+                        large: count(large)?,
+                        medium: count(medium)?,
+                        small: count(small)?,
+                        from_edges,
+                    });
+                }
+                other => return Err(context(format!("unknown entry '{other}'"))),
+            }
+        }
+        Ok(level)
+    }
+}
+
+/// Parses every word as a finite number, expecting a count within `range`.
+fn numbers(words: &[&str], range: std::ops::RangeInclusive<usize>) -> Result<Vec<f64>, String> {
+    if !range.contains(&words.len()) {
+        return Err(format!(
+            "expected {} to {} numbers, got {}",
+            range.start(),
+            range.end(),
+            words.len()
+        ));
+    }
+    words
+        .iter()
+        .map(|word| match word.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(value),
+            _ => Err(format!("'{word}' is not a number")),
+        })
+        .collect()
+}
+
+fn size(word: &str) -> Result<f64, String> {
+    match word {
+        "large" => Ok(LARGE_ASTEROID_SIZE),
+        "medium" => Ok(MEDIUM_ASTEROID_SIZE),
+        "small" => Ok(SMALL_ASTEROID_SIZE),
+        _ => match word.parse::<f64>() {
+            Ok(radius) if radius.is_finite() && radius > 0.0 => Ok(radius),
+            _ => Err(format!("'{word}' is not an asteroid size")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level() {
+        let level = Level::parse(
+            "player 10 20 1.5\n\
+             asteroid 1 2 large; asteroid 3 4 7.5 0.5 -1 # a comment\n\
+             wave 1 2 3 edges",
+        )
+        .unwrap();
+        assert_eq!(level.player, Some((10.0, 20.0, 1.5)));
+        assert_eq!(
+            level.asteroids,
+            vec![
+                PlacedAsteroid { x: 1.0, y: 2.0, size: LARGE_ASTEROID_SIZE, velocity: None },
+                PlacedAsteroid { x: 3.0, y: 4.0, size: 7.5, velocity: Some((0.5, -1.0)) },
+            ]
+        );
+        assert_eq!(level.waves, vec![WaveSpec { large: 1, medium: 2, small: 3, from_edges: true }]);
+        assert_eq!(Level::parse(""), Ok(Level::default()));
+    }
+
+    #[test]
+    fn test_parse_level_errors() {
+        let error = |descriptor| Level::parse(descriptor).unwrap_err();
+        assert_eq!(error("player 1 2\n\nboss 3"), "line 3: unknown entry 'boss'");
+        assert_eq!(error("asteroid 1 2 huge"), "line 1: 'huge' is not an asteroid size");
+        assert_eq!(error("player nan 2"), "line 1: 'nan' is not a number");
+        assert!(error("player 1 2; player 3 4").contains("twice"));
+        assert!(error("asteroid 1 2").contains("got 2 values"));
+        assert!(error("wave 1 2").contains("large medium small"));
+    }
+}
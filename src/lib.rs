@@ -1,32 +1,113 @@
+// Without the `web` feature only the headless core is built, and much of it
+// is reached solely through the browser-facing `Game`.
+#![cfg_attr(not(feature = "web"), allow(dead_code))]
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "web")]
 use wasm_bindgen::prelude::*;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
-use std::f64::consts::TAU;
+#[cfg(feature = "web")]
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, Storage};
+use std::collections::VecDeque;
+use std::f64::consts::{PI, TAU};
+
+#[cfg(feature = "audio")]
+mod audio;
+mod config;
+mod events;
+mod grid;
+mod level;
+#[cfg(feature = "native")]
+pub mod headless;
+mod render;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+mod theme;
+mod timing;
+mod wave;
 
-// When the `wee_alloc` feature is enabled, use `wee_alloc` as the global allocator.
-#[cfg(feature = "wee_alloc")]
-#[global_allocator]
-static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+#[cfg(feature = "web")]
+use config::FeelProfile;
+use config::{GameConfig, RespawnStrategy, WrapMode};
+use events::{EventOverflow, GameEvent};
+use grid::SpatialGrid;
+use level::Level;
+use render::RenderStyle;
+#[cfg(feature = "web")]
+use render::Surface;
+use theme::{Rgba, Theme};
+use timing::FrameTimings;
+use wave::{WaveSpec, WaveStats, MAX_DIFFICULTY, MIN_DIFFICULTY, SMALL_ASTEROID_SIZE};
 
+#[cfg(feature = "web")]
 #[wasm_bindgen(start)]
 pub fn start() {
     #[cfg(debug_assertions)]
     console_error_panic_hook::set_once();
 }
 
+/// The window's `devicePixelRatio`, or 1 outside a window.
+#[cfg(feature = "web")]
+fn window_pixel_ratio() -> f64 {
+    web_sys::window()
+        .map(|window| window.device_pixel_ratio())
+        .filter(|ratio| ratio.is_finite() && *ratio > 0.0)
+        .unwrap_or(1.0)
+}
+
+/// localStorage key the best score is kept under between sessions.
+#[cfg(feature = "web")]
+const HIGH_SCORE_KEY: &str = "asteroids_highscore";
+
+#[cfg(feature = "web")]
 #[wasm_bindgen]
 pub struct Game {
     canvas: HtmlCanvasElement,
     context: CanvasRenderingContext2d,
-    player: Player,
-    asteroids: Vec<Asteroid>,
-    bullets: Vec<Bullet>,
-    score: u32,
+    world: World,
+    observer: Option<js_sys::Function>,
+    pause_renderer: Option<js_sys::Function>,
+    spawn_callback: Option<js_sys::Function>,
+    despawn_callback: Option<js_sys::Function>,
+    capture_callback: Option<js_sys::Function>,
+    /// Where the high score is saved, or `None` when localStorage is
+    /// unavailable and it only lasts as long as the page.
+    storage: Option<Storage>,
+    high_score: u32,
+    /// Pixel ratio set by the host, used instead of the window's.
+    pixel_ratio_override: Option<f64>,
+    /// Opened the first time sound is switched on.
+    #[cfg(feature = "audio")]
+    audio: Option<audio::Audio>,
 }
 
+#[cfg(feature = "web")]
 #[wasm_bindgen]
 impl Game {
+    /// Takes the canvas's current size as the play area, in CSS pixels, and
+    /// scales its backing store by the window's `devicePixelRatio` so
+    /// drawing stays sharp on high-DPI screens.
     #[wasm_bindgen(constructor)]
     pub fn new(canvas: HtmlCanvasElement) -> Game {
+        Game::with_world(canvas, World::new)
+    }
+
+    /// A game whose every random choice follows from `seed`, so the same
+    /// seed always deals the same asteroids. `new` seeds from entropy.
+    pub fn new_seeded(canvas: HtmlCanvasElement, seed: u64) -> Game {
+        Game::with_world(canvas, |bounds| World::seeded(bounds, seed))
+    }
+
+    /// Builds a game from a level descriptor; see the `level` module for
+    /// the format. Malformed descriptors are rejected with a message naming
+    /// the offending line.
+    pub fn from_level(canvas: HtmlCanvasElement, descriptor: &str) -> Result<Game, JsValue> {
+        let level = Level::parse(descriptor).map_err(|e| JsValue::from_str(&e))?;
+        Ok(Game::with_world(canvas, |bounds| World::from_level(bounds, &level)))
+    }
+
+    fn with_world(canvas: HtmlCanvasElement, world: impl FnOnce(Bounds) -> World) -> Game {
         let context = canvas
             .get_context("2d")
             .unwrap()
@@ -34,355 +115,6074 @@ impl Game {
             .dyn_into::<CanvasRenderingContext2d>()
             .unwrap();
 
-        let player = Player::new(
-            canvas.width() as f64 / 2.0,
-            canvas.height() as f64 / 2.0,
-        );
-
-        let mut asteroids = Vec::new();
-        for _ in 0..5 {
-            asteroids.push(Asteroid::new(
-                rand::random::<f64>() * canvas.width() as f64,
-                rand::random::<f64>() * canvas.height() as f64,
-            ));
-        }
+        let bounds = Bounds::new(canvas.width() as f64, canvas.height() as f64);
+        let world = world(bounds);
+        let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+        let high_score = storage
+            .as_ref()
+            .and_then(|storage| storage.get_item(HIGH_SCORE_KEY).ok().flatten())
+            .and_then(|saved| saved.parse().ok())
+            .unwrap_or(0);
 
-        Game {
+        let mut game = Game {
             canvas,
             context,
-            player,
-            asteroids,
-            bullets: Vec::new(),
-            score: 0,
+            world,
+            observer: None,
+            pause_renderer: None,
+            spawn_callback: None,
+            despawn_callback: None,
+            capture_callback: None,
+            storage,
+            high_score,
+            pixel_ratio_override: None,
+            #[cfg(feature = "audio")]
+            audio: None,
+        };
+        // Pin the CSS size first, so growing the backing store below
+        // cannot grow the canvas on the page with it.
+        game.set_css_size(bounds.width, bounds.height);
+        game.resize();
+        game
+    }
+
+    /// Advances the simulation by `dt` seconds, the time since the last
+    /// frame. Steps longer than a quarter second are cut short.
+    pub fn update(&mut self, dt: f64) {
+        let summary = self.world.update(dt);
+        self.record_high_score();
+        self.notify_lifecycle();
+        #[cfg(feature = "audio")]
+        self.play_sounds();
+        self.notify_observer(&summary);
+    }
+
+    /// Draws the current frame. If the canvas has left the document or its
+    /// context stops working, the game pauses and raises `ContextLost`
+    /// rather than failing; call `recover` once the canvas is back.
+    pub fn render(&mut self) -> Result<(), JsValue> {
+        self.check_canvas();
+        self.world.present(&self.context);
+        self.notify_pause_renderer();
+        self.notify_capture();
+        Ok(())
+    }
+
+    /// Advances the simulation by `dt` seconds and draws it, saving the host
+    /// a second trip across the JS/WASM boundary each animation frame.
+    pub fn tick(&mut self, dt: f64) -> Result<(), JsValue> {
+        self.check_canvas();
+        let summary = self.world.tick(&self.context, dt);
+        self.record_high_score();
+        self.notify_lifecycle();
+        #[cfg(feature = "audio")]
+        self.play_sounds();
+        self.notify_observer(&summary);
+        self.notify_pause_renderer();
+        self.notify_capture();
+        Ok(())
+    }
+
+    /// Draws the current frame and returns it as a PNG data URL, for hosts
+    /// to save or share. Fails if the context is lost or the canvas is
+    /// tainted by cross-origin content.
+    pub fn capture_frame(&self) -> Result<String, JsValue> {
+        self.world.capture_frame(&self.context)
+    }
+
+    /// Re-acquires the 2D context after `ContextLost` and resumes the game.
+    pub fn recover(&mut self) -> Result<(), JsValue> {
+        if !self.canvas.is_connected() {
+            return Err(JsValue::from_str("canvas is not attached to the document"));
         }
+        self.context = self
+            .canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("2d context unavailable"))?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        self.world.recover_context();
+        Ok(())
     }
 
-    pub fn update(&mut self) {
-        let (width, height) = (self.canvas.width() as f64, self.canvas.height() as f64);
-        self.player.update((width, height));
-        
-        // Update bullets
-        for bullet in &mut self.bullets {
-            bullet.update();
+    pub fn is_paused(&self) -> bool {
+        self.world.paused
+    }
+
+    /// The ship's position, for minimaps and debug overlays.
+    pub fn player_x(&self) -> f64 {
+        self.world.player.x
+    }
+
+    pub fn player_y(&self) -> f64 {
+        self.world.player.y
+    }
+
+    /// The ship's heading in radians, clockwise from straight up.
+    pub fn player_angle(&self) -> f64 {
+        self.world.player.angle
+    }
+
+    /// How fast the ship is moving, in pixels per second.
+    pub fn player_speed(&self) -> f64 {
+        self.world.player.speed()
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.world.lives
+    }
+
+    /// The best score reached on this browser, this game included. Kept in
+    /// localStorage, or only for the page's lifetime where that is
+    /// unavailable.
+    pub fn high_score(&self) -> u32 {
+        self.high_score
+    }
+
+    /// What is left of the current life's health. Only asteroid hits with
+    /// collision damage on wear it down.
+    pub fn health(&self) -> f64 {
+        self.world.health
+    }
+
+    /// What is left in the tank, from 0 (empty, no thrust) to 1 (full).
+    pub fn fuel(&self) -> f64 {
+        self.world.fuel
+    }
+
+    /// The wave in progress, counting from 1. Each cleared wave brings the
+    /// next, with one more asteroid than the last and a little faster.
+    pub fn level(&self) -> u32 {
+        self.world.wave
+    }
+
+    /// Whether the last life has been lost. Hosts can stop calling `update`
+    /// until `continue_game`.
+    pub fn is_game_over(&self) -> bool {
+        self.world.game_over
+    }
+
+    /// Freezes the simulation; `render` keeps drawing the frozen frame.
+    pub fn pause(&mut self) {
+        self.world.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.world.resume();
+    }
+
+    /// Pauses a running game or resumes a paused one.
+    pub fn toggle_pause(&mut self) {
+        self.world.toggle_pause();
+    }
+
+    /// Registers a function called at the end of every update with a JSON
+    /// summary of the score, lives and asteroid count. Pass `undefined` to
+    /// remove it.
+    pub fn set_frame_observer(&mut self, observer: Option<js_sys::Function>) {
+        self.observer = observer;
+    }
+
+    /// Registers a function called after each update with a JSON
+    /// description (`kind`, `id`, `x`, `y`) of every entity that entered
+    /// the field during it. Pass `undefined` to remove it.
+    pub fn set_spawn_callback(&mut self, callback: Option<js_sys::Function>) {
+        self.spawn_callback = callback;
+        self.world.track_lifecycle = self.has_lifecycle_callbacks();
+    }
+
+    /// Like `set_spawn_callback`, for entities destroyed or collected,
+    /// with their last position.
+    pub fn set_despawn_callback(&mut self, callback: Option<js_sys::Function>) {
+        self.despawn_callback = callback;
+        self.world.track_lifecycle = self.has_lifecycle_callbacks();
+    }
+
+    /// Registers a function called with the frame index after every
+    /// `every`th frame drawn, starting with the next one, so the host can
+    /// grab `capture_frame` or the canvas for a clip. Pass `undefined` to
+    /// stop.
+    pub fn set_capture_callback(&mut self, callback: Option<js_sys::Function>, every: u32) {
+        self.world.capture_interval = if callback.is_some() { every.max(1) } else { 0 };
+        self.world.frame_index = 0;
+        self.capture_callback = callback;
+    }
+
+    /// Registers a function called with the 2D context after each frame
+    /// drawn while paused, so the host can paint its own pause screen over
+    /// the frozen frame. Pass `undefined` to remove it.
+    pub fn set_pause_renderer(&mut self, renderer: Option<js_sys::Function>) {
+        self.pause_renderer = renderer;
+    }
+
+    /// Turns on the built-in pause overlay: the frame is dimmed by `dim`
+    /// (0 to 1) and `text` is written across the middle. Out-of-range dim
+    /// amounts are ignored.
+    pub fn set_pause_overlay(&mut self, dim: f64, text: String) {
+        if (0.0..=1.0).contains(&dim) {
+            self.world.pause_overlay = Some(PauseOverlay { dim, text });
         }
-        
-        // Update asteroids
-        for asteroid in &mut self.asteroids {
-            asteroid.update((width, height));
+    }
+
+    pub fn clear_pause_overlay(&mut self) {
+        self.world.pause_overlay = None;
+    }
+
+    /// Sets which controls are held. Every update then thrusts, turns and
+    /// fires for as long as they stay held, with shots paced by the fire
+    /// cooldown, so the page only has to report key changes. The one-shot
+    /// methods below still act immediately.
+    pub fn set_input(&mut self, thrust: bool, left: bool, right: bool, shoot: bool) {
+        self.world.input = HeldInput {
+            thrust,
+            left,
+            right,
+            shoot,
+        };
+    }
+
+    pub fn shoot(&mut self) {
+        self.world.shoot();
+    }
+
+    /// Jumps the ship to a random spot, at rest. Jumps are limited to one
+    /// every `HYPERSPACE_COOLDOWN` seconds, and each carries the configured
+    /// risk of destroying the ship.
+    pub fn hyperspace(&mut self) {
+        self.world.hyperspace();
+    }
+
+    /// Sets the chance, from 0 to 1, that a hyperspace jump destroys the
+    /// ship. Out-of-range values are ignored.
+    pub fn set_hyperspace_risk(&mut self, risk: f64) {
+        if (0.0..=1.0).contains(&risk) {
+            self.world.config.hyperspace_risk = risk;
+        }
+    }
+
+    pub fn rotate_left(&mut self) {
+        self.world.turn(-1.0);
+    }
+
+    pub fn rotate_right(&mut self) {
+        self.world.turn(1.0);
+    }
+
+    pub fn thrust(&mut self) {
+        self.world.thrust();
+    }
+
+    /// Applies a named bundle of ship handling constants: `"classic"`,
+    /// `"floaty"`, `"arcade"` or `"heavy"`. Returns `false` for unknown
+    /// names and leaves the handling unchanged.
+    pub fn set_feel_profile(&mut self, name: &str) -> bool {
+        match FeelProfile::from_name(name) {
+            Some(profile) => {
+                profile.apply(&mut self.world.config);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Switches to a named color preset such as `"deuteranopia"`.
+    /// Unknown names fall back to the classic palette and return `false`.
+    pub fn set_palette(&mut self, name: &str) -> bool {
+        let known = self.world.theme.apply_palette(name);
+        self.world.refresh_background();
+        known
+    }
+
+    /// Blends the background through these `0xRRGGBB` colors as the waves
+    /// go by, a few waves per color. An empty list goes back to a clear
+    /// canvas.
+    pub fn set_wave_backgrounds(&mut self, colors: Vec<u32>) {
+        self.world.theme.wave_backgrounds = colors.into_iter().map(Rgba::from_hex).collect();
+        self.world.refresh_background();
+    }
+
+    /// Caps asteroid speed in pixels per second. Non-positive or non-finite
+    /// values are ignored.
+    pub fn set_max_asteroid_speed(&mut self, speed: f64) {
+        if speed.is_finite() && speed > 0.0 {
+            self.world.config.max_asteroid_speed = speed;
+        }
+    }
+
+    /// Scales how fast split fragments fly relative to their parent.
+    /// Negative or non-finite values are ignored.
+    pub fn set_split_speed_multiplier(&mut self, multiplier: f64) {
+        if multiplier.is_finite() && multiplier >= 0.0 {
+            self.world.config.split_speed_multiplier = multiplier;
+        }
+    }
+
+    /// Blends split fragments from inheriting the parent's velocity (0, the
+    /// default) to fresh velocities in random directions (1). Out-of-range
+    /// values are ignored.
+    pub fn set_split_randomness(&mut self, randomness: f64) {
+        if (0.0..=1.0).contains(&randomness) {
+            self.world.config.split_randomness = randomness;
+        }
+    }
+
+    /// Sets how far entities may drift past an edge before wrapping.
+    /// Negative or non-finite values are ignored.
+    pub fn set_wrap_buffer(&mut self, buffer: f64) {
+        if buffer.is_finite() && buffer >= 0.0 {
+            self.world.config.wrap_buffer = buffer;
+        }
+    }
+
+    /// Sets how edge-spawned waves enter: the most their heading may stray
+    /// from straight inward, in radians, and their entry speed. Spreads of a
+    /// right angle or more could point rocks back out and are ignored, as
+    /// are negative or non-finite values.
+    pub fn set_edge_spawn(&mut self, spread: f64, speed: f64) {
+        if spread.is_finite() && (0.0..PI / 2.0).contains(&spread) {
+            self.world.config.edge_spawn_spread = spread;
+        }
+        if speed.is_finite() && speed >= 0.0 {
+            self.world.config.edge_entry_speed = speed;
+        }
+    }
+
+    /// Makes asteroids bounce off each other, heavier rocks deflecting less.
+    /// Off by default.
+    pub fn set_asteroid_collisions(&mut self, enabled: bool) {
+        self.world.config.asteroid_collisions = enabled;
+    }
+
+    /// Draws motion trails behind asteroids faster than `threshold` pixels
+    /// per second, longer the faster they go. Off by default. A negative or
+    /// non-finite threshold is ignored.
+    pub fn set_asteroid_trails(&mut self, enabled: bool, threshold: f64) {
+        self.world.config.asteroid_trails = enabled;
+        if threshold.is_finite() && threshold >= 0.0 {
+            self.world.config.trail_speed_threshold = threshold;
+        }
+    }
+
+    /// Spreads each new wave's arrival out, one asteroid every `seconds`.
+    /// Zero spawns the whole wave at once. Negative or non-finite values are
+    /// ignored.
+    pub fn set_wave_intro_interval(&mut self, seconds: f64) {
+        if seconds.is_finite() && seconds >= 0.0 {
+            self.world.config.wave_intro_interval = seconds;
+        }
+    }
+
+    /// Makes asteroid hits cost health instead of a whole life. Each hit
+    /// deals `per_size` damage per pixel of the rock's radius plus
+    /// `per_speed` per pixel per second of closing speed. Negative or
+    /// non-finite rates are ignored.
+    pub fn set_collision_damage(&mut self, enabled: bool, per_size: f64, per_speed: f64) {
+        self.world.config.collision_damage = enabled;
+        if per_size.is_finite() && per_size >= 0.0 {
+            self.world.config.damage_per_size = per_size;
+        }
+        if per_speed.is_finite() && per_speed >= 0.0 {
+            self.world.config.damage_per_speed = per_speed;
+        }
+    }
+
+    /// Sets how fast fuel drains and refills, each as a share of a full
+    /// tank: `burn` per second of thrust (0 for unlimited fuel), `per_shot`
+    /// for every shot and `regen` per second off the throttle. Negative or
+    /// non-finite rates are ignored.
+    pub fn set_fuel_rates(&mut self, burn: f64, per_shot: f64, regen: f64) {
+        let config = &mut self.world.config;
+        for (rate, value) in [
+            (&mut config.fuel_burn_rate, burn),
+            (&mut config.fuel_per_shot, per_shot),
+            (&mut config.fuel_regen_rate, regen),
+        ] {
+            if value.is_finite() && value >= 0.0 {
+                *rate = value;
+            }
+        }
+    }
+
+    /// Sets the health each life starts with and refills the current one.
+    /// Non-positive or non-finite values are ignored.
+    pub fn set_max_health(&mut self, health: f64) {
+        if health.is_finite() && health > 0.0 {
+            self.world.config.max_health = health;
+            self.world.health = health;
+        }
+    }
+
+    /// Turns optional entity types on or off, for hosts that want only the
+    /// core loop. Everything is on by default.
+    pub fn set_entity_toggles(&mut self, ufos: bool, power_ups: bool, particles: bool) {
+        self.world.config.ufos_enabled = ufos;
+        self.world.config.power_ups_enabled = power_ups;
+        self.world.config.particles_enabled = particles;
+    }
+
+    /// Blows up the asteroids with these ids one per frame, in order, no
+    /// bullets needed. Ids come from `entities_in_rect`; ones that no longer
+    /// exist are skipped.
+    pub fn queue_destruction(&mut self, ids: Vec<u32>) {
+        self.world.destruction_queue.extend(ids);
+    }
+
+    /// Switches on the magnet, which pulls nearby small asteroids in to be
+    /// collected for bonus points.
+    pub fn activate_magnet(&mut self) {
+        self.world.activate_magnet();
+    }
+
+    /// Lets asteroid numbers and speed follow how well the player is doing,
+    /// rising with accuracy and survival and falling after deaths. Off by
+    /// default.
+    pub fn set_adaptive_difficulty(&mut self, enabled: bool) {
+        self.world.config.adaptive_difficulty = enabled;
+    }
+
+    /// Multiplier currently applied to new waves' asteroid count and speed.
+    pub fn difficulty(&self) -> f64 {
+        self.world.difficulty
+    }
+
+    /// Makes each shot a burst of `pellets` bullets fanned across
+    /// `spread_angle` radians. Bursts are cut short rather than exceed the
+    /// bullet cap. Zero pellets or a negative arc are ignored.
+    pub fn set_spread(&mut self, pellets: u32, spread_angle: f64) {
+        if pellets > 0 && spread_angle.is_finite() && spread_angle >= 0.0 {
+            self.world.config.pellets = pellets;
+            self.world.config.spread_angle = spread_angle;
+        }
+    }
+
+    /// Angles the engine away from the nose by `radians`, so thrust pushes
+    /// the ship off its heading. Non-finite values are ignored.
+    pub fn set_thrust_offset(&mut self, radians: f64) {
+        if radians.is_finite() {
+            self.world.config.thrust_offset = radians;
+        }
+    }
+
+    /// Makes turning ramp up and coast to a stop instead of starting and
+    /// stopping instantly. Off by default.
+    pub fn set_turning_inertia(&mut self, enabled: bool) {
+        self.world.config.turning_inertia = enabled;
+    }
+
+    /// Keeps new asteroids out of a cone ahead of the ship, `radians` either
+    /// side of its heading. Zero turns the protection off; negative or
+    /// non-finite values are ignored.
+    pub fn set_spawn_safe_cone(&mut self, radians: f64) {
+        if radians.is_finite() && radians >= 0.0 {
+            self.world.config.spawn_safe_cone = radians;
+        }
+    }
+
+    /// Awards a bonus for each asteroid that skims past the ship. Off by
+    /// default.
+    pub fn set_near_miss_scoring(&mut self, enabled: bool) {
+        self.world.config.near_miss_scoring = enabled;
+    }
+
+    /// Makes asteroids breakable only on the side facing the ship; other
+    /// hits glance off. Off by default.
+    pub fn set_weak_spot_mode(&mut self, enabled: bool) {
+        self.world.config.weak_spot_mode = enabled;
+    }
+
+    /// Accelerates bullets along `(x, y)`, in pixels per second squared, for
+    /// lobbed shots. `(0, 0)` turns it off. Non-finite values are ignored.
+    pub fn set_bullet_gravity(&mut self, x: f64, y: f64) {
+        if x.is_finite() && y.is_finite() {
+            self.world.config.bullet_gravity = (x, y);
+        }
+    }
+
+    /// Bends shots toward the nearest asteroid within `cone` radians of the
+    /// nose, by `strength` from 0 (off, the default) to 1 (dead on). Values
+    /// out of range or non-finite are ignored.
+    pub fn set_aim_assist(&mut self, strength: f64, cone: f64) {
+        if (0.0..=1.0).contains(&strength) {
+            self.world.config.aim_assist = strength;
+        }
+        if cone.is_finite() && cone >= 0.0 {
+            self.world.config.aim_assist_cone = cone;
+        }
+    }
+
+    /// Lets player bullets shoot down enemy bullets. Off by default.
+    pub fn set_bullet_interception(&mut self, enabled: bool) {
+        self.world.config.bullet_interception = enabled;
+    }
+
+    /// Sets how long a fresh bullet flies before it can hit anything.
+    /// Negative or non-finite values are ignored.
+    pub fn set_bullet_arming_time(&mut self, seconds: f64) {
+        if seconds.is_finite() && seconds >= 0.0 {
+            self.world.config.bullet_arming_time = seconds;
+        }
+    }
+
+    /// Chooses where the ship respawns: `"center"`, `"safest_point"` or
+    /// `"last_safe_position"`. Returns `false` for unknown names and leaves
+    /// the strategy unchanged.
+    pub fn set_respawn_strategy(&mut self, name: &str) -> bool {
+        match RespawnStrategy::from_name(name) {
+            Some(strategy) => {
+                self.world.config.respawn_strategy = strategy;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Chooses what the ship does at the edges: `"wrap"` (the default),
+    /// `"clamp"` or `"bounce"`. Returns `false` for unknown names and leaves
+    /// the mode unchanged.
+    pub fn set_wrap_mode(&mut self, name: &str) -> bool {
+        match WrapMode::from_name(name) {
+            Some(mode) => {
+                self.world.config.wrap_mode = mode;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Restores lives after a game over, trading away part of the score.
+    pub fn continue_game(&mut self) {
+        self.world.continue_game();
+    }
+
+    /// Starts a new game on the same canvas: score, lives, waves and the
+    /// asteroid field all start over, while the config, theme and other
+    /// settings made through this API are kept.
+    pub fn restart(&mut self) {
+        self.world.restart();
+    }
+
+    pub fn continues(&self) -> u32 {
+        self.world.continues
+    }
+
+    /// Sets how many lives a continue grants and the fraction of the score
+    /// it keeps. Factors outside `0..=1` are ignored.
+    pub fn set_continue_rules(&mut self, lives: u32, score_factor: f64) {
+        self.world.config.continue_lives = lives.max(1);
+        if (0.0..=1.0).contains(&score_factor) {
+            self.world.config.continue_score_factor = score_factor;
+        }
+    }
+
+    /// The multiplier on the next kill's points, 1 unless asteroids have
+    /// been falling in quick succession.
+    pub fn combo(&self) -> u32 {
+        self.world.combo
+    }
+
+    /// The running score, readable from JavaScript as `game.score`.
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> u32 {
+        self.world.score
+    }
+
+    pub fn bullet_count(&self) -> usize {
+        self.world.bullets.len()
+    }
+
+    /// Bullets the bullet storage can hold before it reallocates.
+    pub fn bullet_capacity(&self) -> usize {
+        self.world.bullets.capacity()
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.world.particles.len()
+    }
+
+    pub fn asteroid_count(&self) -> usize {
+        self.world.asteroids.len()
+    }
+
+    /// Asteroids the asteroid storage can hold before it reallocates.
+    pub fn asteroid_capacity(&self) -> usize {
+        self.world.asteroids.capacity()
+    }
+
+    /// Threat from nearby asteroids in `0..=1`, for HUD tints or music.
+    pub fn danger_level(&self) -> f64 {
+        self.world.danger_level()
+    }
+
+    /// Restores the simulation to how it was `steps` updates ago, bounded by
+    /// the recorded history. Returns the number of steps rewound.
+    pub fn rewind(&mut self, steps: usize) -> usize {
+        self.world.rewind(steps)
+    }
+
+    /// Moves the ship to `(x, y)` with the given heading and velocity, e.g.
+    /// to set up a scripted scenario. Fails on non-finite values.
+    pub fn place_player(
+        &mut self,
+        x: f64,
+        y: f64,
+        heading: f64,
+        velocity_x: f64,
+        velocity_y: f64,
+    ) -> Result<(), JsValue> {
+        self.world.player = Player::builder(x, y)
+            .heading(heading)
+            .velocity(velocity_x, velocity_y)
+            .build()
+            .map_err(|e| JsValue::from_str(&e))?;
+        Ok(())
+    }
+
+    /// Checks every entity for non-finite positions or velocities, which
+    /// wild tuning can produce. The error lists each offender, one per line.
+    pub fn validate(&self) -> Result<(), JsValue> {
+        self.world
+            .validate()
+            .map_err(|problems| JsValue::from_str(&problems.join("\n")))
+    }
+
+    /// The smallest box around every asteroid and the ship, as
+    /// `[x, y, w, h]`, for framing the action or scaling a minimap. With no
+    /// asteroids left it is the whole play area.
+    pub fn field_bounds(&self) -> Vec<f64> {
+        let (x, y, w, h) = self.world.field_bounds();
+        vec![x, y, w, h]
+    }
+
+    /// Lists the entities overlapping a rectangle as a JSON array of
+    /// `{ kind, index, id, x, y, radius, velocity_x, velocity_y }`, with `id`
+    /// only on asteroids. Rectangles crossing an edge wrap.
+    pub fn entities_in_rect(&self, x: f64, y: f64, w: f64, h: f64) -> String {
+        let entities = self.world.entities_in_rect((x, y, w, h));
+        serde_json::to_string(&entities).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Returns and clears the events raised since the last call, as a JSON
+    /// array of objects tagged by `type`.
+    pub fn drain_events(&mut self) -> String {
+        let events: Vec<GameEvent> = self.world.events.drain(..).collect();
+        serde_json::to_string(&events).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// The score, wave reached, accuracy, seconds survived and seed as JSON,
+    /// for submitting to a leaderboard. The seed is a string.
+    pub fn score_summary(&self) -> String {
+        serde_json::to_string(&self.world.score_summary()).unwrap_or_default()
+    }
+
+    /// The state of the game's random generator, as JSON. Saving it with
+    /// a game and restoring it later replays the same future randomness.
+    pub fn rng_state(&self) -> String {
+        self.world.rng_state()
+    }
+
+    /// Restores a state captured by `rng_state`. Invalid input is an error
+    /// and leaves the generator untouched.
+    pub fn set_rng_state(&mut self, json: &str) -> Result<(), JsValue> {
+        self.world.set_rng_state(json).map_err(|e| JsValue::from_str(&e))
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.world.events.len()
+    }
+
+    /// Discards every pending event without reading it.
+    pub fn clear_events(&mut self) {
+        self.world.events.clear();
+    }
+
+    pub fn event_log_cap(&self) -> usize {
+        self.world.config.event_log_cap
+    }
+
+    /// Caps how many undrained events are kept, trimming the oldest if the
+    /// log is already longer.
+    pub fn set_event_log_cap(&mut self, cap: usize) {
+        self.world.config.event_log_cap = cap;
+        while self.world.events.len() > cap {
+            self.world.events.pop_front();
+        }
+    }
+
+    /// Chooses what a full log discards: `"drop_oldest"` or
+    /// `"drop_newest"`. Returns `false` for unknown names and leaves the
+    /// policy unchanged.
+    pub fn set_event_overflow(&mut self, name: &str) -> bool {
+        match EventOverflow::from_name(name) {
+            Some(policy) => {
+                self.world.config.event_overflow = policy;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Picks up the canvas's current CSS size as the play area and resizes
+    /// its backing store to that times the pixel ratio, which is read from
+    /// the window again unless the host has set one. Call after changing
+    /// the canvas's CSS size or moving to another screen. Anything left
+    /// outside a smaller area is pulled back to its edge.
+    pub fn resize(&mut self) {
+        let (width, height) = self.css_size();
+        let ratio = self.pixel_ratio_override.unwrap_or_else(window_pixel_ratio);
+        self.world.pixel_ratio = ratio;
+        let (backing_width, backing_height) =
+            ((width * ratio).round() as u32, (height * ratio).round() as u32);
+        if (self.canvas.width(), self.canvas.height()) != (backing_width, backing_height) {
+            self.canvas.set_width(backing_width);
+            self.canvas.set_height(backing_height);
+            self.set_css_size(width, height);
+        }
+        let canvas = Bounds::new(width, height);
+        match self.world.view {
+            Some(_) => self.world.view = Some(canvas),
+            None => self.world.resize(canvas),
+        }
+    }
+
+    /// Makes the play field `width` by `height`, independent of the canvas.
+    /// Things wrap at the field's edges, and a camera on the ship scrolls a
+    /// canvas-sized window over it. Non-positive or non-finite sizes are
+    /// ignored.
+    pub fn set_world_size(&mut self, width: f64, height: f64) {
+        if !(width.is_finite() && height.is_finite() && width > 0.0 && height > 0.0) {
+            return;
+        }
+        let ratio = self.world.pixel_ratio;
+        self.world.view = Some(Bounds::new(
+            self.canvas.width() as f64 / ratio,
+            self.canvas.height() as f64 / ratio,
+        ));
+        self.world.resize(Bounds::new(width, height));
+    }
+
+    /// Starts or stops recording how long `tick` spends updating and
+    /// rendering. Stopping discards what was recorded.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.world.timings = enabled.then(FrameTimings::default);
+    }
+
+    /// A histogram of recent update and render times per frame, as JSON.
+    /// Empty until profiling is switched on.
+    pub fn timing_report(&self) -> String {
+        let report = self.world.timings.clone().unwrap_or_default().report();
+        serde_json::to_string(&report).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Confines drawing and clearing to the rectangle at `(x, y)` sized `w`
+    /// by `h` in CSS pixels, so several games can share one canvas. The play
+    /// area and its wrapping are unchanged. Empty or non-finite rectangles
+    /// are ignored.
+    pub fn set_viewport(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        if [x, y, w, h].iter().all(|v| v.is_finite()) && w > 0.0 && h > 0.0 {
+            self.world.viewport = Some((x, y, w, h));
+        }
+    }
+
+    /// Goes back to drawing over the whole canvas.
+    pub fn reset_viewport(&mut self) {
+        self.world.viewport = None;
+    }
+
+    /// Overrides how many canvas pixels make up one CSS pixel, which is
+    /// otherwise the window's `devicePixelRatio`. The backing store is
+    /// resized to match; the play area stays in CSS pixels. Non-positive or
+    /// non-finite ratios are ignored.
+    pub fn set_device_pixel_ratio(&mut self, ratio: f64) {
+        if ratio.is_finite() && ratio > 0.0 {
+            self.pixel_ratio_override = Some(ratio);
+            self.resize();
+        }
+    }
+
+    /// The pixel ratio drawing is scaled by.
+    pub fn device_pixel_ratio(&self) -> f64 {
+        self.world.pixel_ratio
+    }
+
+    /// Replaces the wave script with a JSON array of
+    /// `{ "large": n, "medium": n, "small": n }` objects. Waves past the end
+    /// repeat the last entry with an extra large asteroid each time.
+    pub fn set_waves(&mut self, json: &str) -> Result<(), JsValue> {
+        let waves: Vec<WaveSpec> =
+            serde_json::from_str(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if waves.is_empty() {
+            return Err(JsValue::from_str("at least one wave is required"));
+        }
+        self.world.waves = waves;
+        Ok(())
+    }
+
+    /// Replaces every tuning value with those in `json`, as produced by
+    /// `export_config`. Missing fields take their defaults. Invalid JSON or
+    /// values leave the current config untouched.
+    pub fn apply_config(&mut self, json: &str) -> Result<(), JsValue> {
+        self.world.config = GameConfig::from_json(json).map_err(|e| JsValue::from_str(&e))?;
+        Ok(())
+    }
+
+    /// The current tuning values as JSON.
+    pub fn export_config(&self) -> String {
+        self.world.config.to_json()
+    }
+
+    /// The ship, asteroids, saucers and bullets with their positions and
+    /// velocities, plus the score, lives and wave, as JSON for saving or
+    /// replaying. Settings are left out; see `export_config`.
+    pub fn to_json(&self) -> String {
+        self.world.to_json()
+    }
+
+    /// Restores a game saved with `to_json` in place, keeping the canvas
+    /// and settings. Malformed saves are rejected and change nothing.
+    pub fn load_json(&mut self, json: &str) -> Result<(), JsValue> {
+        self.world.load_json(json).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Switches between `"wireframe"` outlines and `"filled"` shapes.
+    /// Returns `false` for unknown names and leaves the style unchanged.
+    pub fn set_render_style(&mut self, name: &str) -> bool {
+        match RenderStyle::from_name(name) {
+            Some(style) => {
+                self.world.render_style = style;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the outline color for the ship, any CSS color. Blank strings
+    /// are ignored.
+    pub fn set_ship_color(&mut self, color: String) {
+        theme::replace_color(&mut self.world.theme.ship, &color);
+    }
+
+    /// Sets the outline color for asteroids. Blank strings are ignored.
+    pub fn set_asteroid_color(&mut self, color: String) {
+        theme::replace_color(&mut self.world.theme.asteroid, &color);
+    }
+
+    /// Sets the fill color for bullets. Blank strings are ignored.
+    pub fn set_bullet_color(&mut self, color: String) {
+        theme::replace_color(&mut self.world.theme.bullet, &color);
+    }
+
+    /// Sets the color for flying saucers and their shots. Blank strings are
+    /// ignored.
+    pub fn set_ufo_color(&mut self, color: String) {
+        theme::replace_color(&mut self.world.theme.ufo, &color);
+    }
+
+    /// Saves the score if it beats the best so far. Storage that refuses
+    /// the write, as in some private browsing modes, is dropped in favour of
+    /// keeping the value in memory.
+    fn record_high_score(&mut self) {
+        if self.world.score <= self.high_score {
+            return;
+        }
+        self.high_score = self.world.score;
+        if let Some(storage) = &self.storage {
+            if storage.set_item(HIGH_SCORE_KEY, &self.high_score.to_string()).is_err() {
+                self.storage = None;
+            }
+        }
+    }
+
+    /// The canvas's size on the page, from layout once it is displayed and
+    /// otherwise worked back from the backing store.
+    fn css_size(&self) -> (f64, f64) {
+        let (width, height) = (self.canvas.client_width(), self.canvas.client_height());
+        if width > 0 && height > 0 {
+            return (width as f64, height as f64);
+        }
+        let ratio = self.world.pixel_ratio;
+        (self.canvas.width() as f64 / ratio, self.canvas.height() as f64 / ratio)
+    }
+
+    fn set_css_size(&self, width: f64, height: f64) {
+        let style = self.canvas.style();
+        // Only fails on read-only declarations, which a canvas's is not.
+        let _ = style.set_property("width", &format!("{width}px"));
+        let _ = style.set_property("height", &format!("{height}px"));
+    }
+
+    fn check_canvas(&mut self) {
+        if !self.canvas.is_connected() {
+            self.world.lose_context();
+        }
+    }
+
+    fn notify_pause_renderer(&self) {
+        if !self.world.paused || self.world.context_lost {
+            return;
+        }
+        let Some(renderer) = &self.pause_renderer else {
+            return;
+        };
+        if let Err(e) = renderer.call1(&JsValue::NULL, &self.context) {
+            web_sys::console::error_1(&e);
+        }
+    }
+
+    /// Turns the shot and explosion sound effects on or off. Browsers only
+    /// let audio start after a user gesture, so call this from an input
+    /// handler. Where Web Audio is unavailable the game stays silent.
+    #[cfg(feature = "audio")]
+    pub fn set_sound(&mut self, enabled: bool) {
+        if enabled && self.audio.is_none() {
+            self.audio = audio::Audio::new();
+        }
+        self.world.sound = enabled && self.audio.is_some();
+        self.world.sound_cues.clear();
+    }
+
+    #[cfg(feature = "audio")]
+    fn play_sounds(&mut self) {
+        let cues = std::mem::take(&mut self.world.sound_cues);
+        if let Some(audio) = &self.audio {
+            for cue in cues {
+                audio.play(cue);
+            }
+        }
+    }
+
+    fn has_lifecycle_callbacks(&self) -> bool {
+        self.spawn_callback.is_some() || self.despawn_callback.is_some()
+    }
+
+    fn notify_capture(&mut self) {
+        let Some(index) = self.world.advance_capture() else {
+            return;
+        };
+        let Some(callback) = &self.capture_callback else {
+            return;
+        };
+        if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from_f64(index as f64)) {
+            web_sys::console::error_1(&e);
+        }
+    }
+
+    fn notify_lifecycle(&mut self) {
+        for event in std::mem::take(&mut self.world.lifecycle) {
+            let callback = match event.change {
+                Lifecycle::Spawned => &self.spawn_callback,
+                Lifecycle::Despawned => &self.despawn_callback,
+            };
+            let (Some(callback), Ok(json)) = (callback, serde_json::to_string(&event)) else {
+                continue;
+            };
+            if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from_str(&json)) {
+                web_sys::console::error_1(&e);
+            }
+        }
+    }
+
+    fn notify_observer(&self, summary: &FrameSummary) {
+        // Observers can only be registered from JavaScript, so this never
+        // runs in native builds.
+        let Some(observer) = &self.observer else {
+            return;
+        };
+        let Ok(json) = serde_json::to_string(summary) else {
+            return;
+        };
+        if let Err(e) = observer.call1(&JsValue::NULL, &JsValue::from_str(&json)) {
+            web_sys::console::error_1(&e);
+        }
+    }
+}
+
+const STARTING_LIVES: u32 = 3;
+
+/// Asteroids farther than this from the ship add nothing to the danger level.
+const DANGER_RADIUS: f64 = 200.0;
+
+/// Combined proximity weight at which the danger level saturates at 1.
+const DANGER_SATURATION: f64 = 3.0;
+
+/// The size of the play area. Kept on `World` so the simulation never has
+/// to ask the canvas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Bounds {
+    width: f64,
+    height: f64,
+}
+
+impl Bounds {
+    fn new(width: f64, height: f64) -> Bounds {
+        Bounds { width, height }
+    }
+}
+
+/// Radius of the ship for collisions and wrapping.
+const SHIP_RADIUS: f64 = 20.0;
+
+/// Wraps a coordinate back onto `[0, span)` once it strays more than `buffer`
+/// past either edge, in a single step however far out it is. Inside the
+/// buffer the coordinate is left alone so the entity can glide across the
+/// seam while its ghost is drawn on the far side.
+fn wrap(value: f64, span: f64, buffer: f64) -> f64 {
+    if value < -buffer || value >= span + buffer {
+        value.rem_euclid(span)
+    } else {
+        value
+    }
+}
+
+/// Keeps `position` at least `margin` inside both ends of `span`, returning
+/// the new position and velocity. `Clamp` stops at the limit and `Bounce`
+/// reflects off it; `Wrap` leaves both untouched, since wrapping is `wrap`'s
+/// job.
+fn confine(position: f64, velocity: f64, span: f64, margin: f64, mode: WrapMode) -> (f64, f64) {
+    let low = margin.min(span / 2.0);
+    let high = span - low;
+    let (limit, outward) = if position < low {
+        (low, velocity < 0.0)
+    } else if position > high {
+        (high, velocity > 0.0)
+    } else {
+        return (position, velocity);
+    };
+    match mode {
+        WrapMode::Wrap => (position, velocity),
+        WrapMode::Clamp => (limit, if outward { 0.0 } else { velocity }),
+        WrapMode::Bounce => {
+            let reflected = (2.0 * limit - position).clamp(low, high);
+            (reflected, if outward { -velocity } else { velocity })
+        }
+    }
+}
+
+/// Translations at which an entity must be drawn so that it also shows on
+/// the opposite edge while it straddles a seam. Always includes `(0, 0)`.
+fn wrap_offsets((x, y, radius): (f64, f64, f64), bounds: Bounds) -> Vec<(f64, f64)> {
+    fn shifts(center: f64, radius: f64, span: f64) -> Vec<f64> {
+        let mut shifts = vec![0.0];
+        if center - radius < 0.0 {
+            shifts.push(span);
+        }
+        if center + radius > span {
+            shifts.push(-span);
+        }
+        shifts
+    }
+    let ys = shifts(y, radius, bounds.height);
+    shifts(x, radius, bounds.width)
+        .into_iter()
+        .flat_map(|dx| ys.iter().map(move |&dy| (dx, dy)))
+        .collect()
+}
+
+/// Draws an entity at its position and at every ghost position across the
+/// wrap seams.
+#[cfg(feature = "web")]
+fn draw_wrapped(
+    surface: &impl Surface,
+    circle: (f64, f64, f64),
+    bounds: Bounds,
+    draw: impl Fn() -> Result<(), JsValue>,
+) -> Result<(), JsValue> {
+    for (dx, dy) in wrap_offsets(circle, bounds) {
+        if (dx, dy) == (0.0, 0.0) {
+            draw()?;
+            continue;
+        }
+        surface.save();
+        surface.translate(dx, dy)?;
+        draw()?;
+        surface.restore();
+    }
+    Ok(())
+}
+
+/// Angles, relative to the nose, of `pellets` bullets spread evenly from
+/// one edge of an `arc` radians wide to the other. A lone pellet flies
+/// straight ahead.
+fn pellet_offsets(pellets: u32, arc: f64) -> impl Iterator<Item = f64> {
+    let step = if pellets > 1 { arc / (pellets - 1) as f64 } else { 0.0 };
+    let first = if pellets > 1 { -arc / 2.0 } else { 0.0 };
+    (0..pellets).map(move |i| first + step * i as f64)
+}
+
+/// The shortest signed offset from `b` to `a` on the wrapping play field.
+fn toroidal_delta((ax, ay): (f64, f64), (bx, by): (f64, f64), bounds: Bounds) -> (f64, f64) {
+    fn wrapped(delta: f64, span: f64) -> f64 {
+        let delta = delta.rem_euclid(span);
+        if delta > span / 2.0 {
+            delta - span
+        } else {
+            delta
+        }
+    }
+    (wrapped(ax - bx, bounds.width), wrapped(ay - by, bounds.height))
+}
+
+/// Shortest distance between two points on the wrapping play field.
+fn toroidal_distance(a: (f64, f64), b: (f64, f64), bounds: Bounds) -> f64 {
+    let (dx, dy) = toroidal_delta(a, b, bounds);
+    dx.hypot(dy)
+}
+
+/// The handful of values a HUD needs each frame, handed to the frame observer.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+struct FrameSummary {
+    score: u32,
+    lives: u32,
+    wave: u32,
+    asteroids: usize,
+}
+
+/// What a host submits to a leaderboard: the outcome of a run, plus the
+/// seed it started from so a server can replay it to check.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct ScoreSummary {
+    score: u32,
+    wave: u32,
+    /// Share of all shots fired that hit.
+    accuracy: f64,
+    /// Seconds played before the game ended, or so far.
+    time_survived: f64,
+    /// Written as a string, since JavaScript numbers cannot hold every `u64`.
+    #[serde(serialize_with = "serialize_seed")]
+    seed: u64,
+}
+
+fn serialize_seed<S: serde::Serializer>(seed: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&seed.to_string())
+}
+
+/// Which collection an `EntityRef` points into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum EntityKind {
+    Player,
+    Asteroid,
+    Bullet,
+}
+
+/// A lightweight description of one entity, returned by region queries.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+struct EntityRef {
+    kind: EntityKind,
+    index: usize,
+    /// The asteroid's stable id; other kinds have none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<u32>,
+    x: f64,
+    y: f64,
+    radius: f64,
+    /// Velocity in pixels per second, for tools that visualize momentum.
+    velocity_x: f64,
+    velocity_y: f64,
+}
+
+/// A sound effect the simulation asks the host to play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SoundCue {
+    Shoot,
+    Explosion,
+}
+
+/// Whether a `LifecycleEvent` reports an entity arriving or leaving.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Lifecycle {
+    Spawned,
+    Despawned,
+}
+
+/// An entity entering or leaving the field, queued for the host's spawn and
+/// despawn callbacks.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+struct LifecycleEvent {
+    #[serde(skip)]
+    change: Lifecycle,
+    kind: EntityKind,
+    id: u32,
+    x: f64,
+    y: f64,
+}
+
+impl LifecycleEvent {
+    fn asteroid(change: Lifecycle, asteroid: &Asteroid) -> LifecycleEvent {
+        LifecycleEvent {
+            change,
+            kind: EntityKind::Asteroid,
+            id: asteroid.id,
+            x: asteroid.x,
+            y: asteroid.y,
+        }
+    }
+}
+
+/// Whether a circle touches a rectangle on the wrapping play field, so a
+/// rectangle hanging off one edge also catches entities on the other side.
+fn circle_in_rect(
+    (cx, cy, radius): (f64, f64, f64),
+    (x, y, w, h): (f64, f64, f64, f64),
+    bounds: Bounds,
+) -> bool {
+    fn overlaps(center: f64, start: f64, length: f64, radius: f64, span: f64) -> bool {
+        let offset = (center - start).rem_euclid(span);
+        offset <= length + radius || offset >= span - radius
+    }
+    overlaps(cx, x, w, radius, bounds.width) && overlaps(cy, y, h, radius, bounds.height)
+}
+
+/// Columns and rows of candidate points searched for the safest respawn.
+const RESPAWN_GRID: usize = 20;
+
+/// The ship counts as safe while no asteroid is nearer than this.
+const SAFE_DISTANCE: f64 = 120.0;
+
+/// Points for an asteroid skimming past the ship.
+const NEAR_MISS_BONUS: u32 = 50;
+
+/// Seconds a magnet pickup stays active.
+const MAGNET_DURATION: f64 = 5.0;
+
+/// How close a small asteroid must be for the magnet to pull it.
+const MAGNET_RANGE: f64 = 150.0;
+
+/// Acceleration the magnet gives a small asteroid towards the ship, in
+/// pixels per second squared.
+const MAGNET_PULL: f64 = 540.0;
+
+/// Points for sweeping up a small asteroid with the magnet.
+const MAGNET_BONUS: u32 = 250;
+
+/// Stars scattered behind the play field.
+const STAR_COUNT: usize = 120;
+
+/// Opacity of the brightest star, kept low so they never read as debris.
+const STAR_MAX_ALPHA: f64 = 0.6;
+
+/// Share of the ship's velocity the starfield drifts at, the other way.
+const STARFIELD_PARALLAX: f64 = 0.05;
+
+/// PCG stream the starfield is drawn from, kept apart from the
+/// simulation's so seeded games play out the same with or without it.
+const STARFIELD_STREAM: u64 = 0x57a2;
+
+/// New asteroids never appear closer than this to the ship.
+const SPAWN_CLEARANCE: f64 = 100.0;
+
+/// Positions tried for each new asteroid before settling for one in a
+/// protected area.
+const SPAWN_ATTEMPTS: u32 = 32;
+
+/// The frame length that per-frame fractions such as damping are tuned
+/// against.
+const REFERENCE_FRAME: f64 = 1.0 / 60.0;
+
+/// Longest step a single update simulates, in seconds. Longer gaps, such
+/// as a tab coming back from the background, are cut short rather than
+/// simulated in one jump.
+const MAX_STEP: f64 = 0.25;
+
+/// Number of past updates kept for `rewind`.
+const REWIND_CAPACITY: usize = 300;
+
+/// The entity and scoring state needed to restore the simulation to an
+/// earlier frame. Also the save format behind `to_json` and `load_json`.
+#[derive(Clone, Serialize, Deserialize)]
+struct GameState {
+    player: Player,
+    asteroids: Vec<Asteroid>,
+    bullets: Vec<Bullet>,
+    ufos: Vec<Ufo>,
+    wave: u32,
+    wave_stats: WaveStats,
+    played: WaveStats,
+    score: u32,
+    lives: u32,
+    health: f64,
+    fuel: f64,
+    game_over: bool,
+    magnet_time: f64,
+    combo: u32,
+    combo_timer: f64,
+    difficulty: f64,
+}
+
+/// Controls held down, applied on every update until released.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct HeldInput {
+    thrust: bool,
+    left: bool,
+    right: bool,
+    shoot: bool,
+}
+
+/// The simulation behind `Game`, kept free of DOM access so it can be driven
+/// and rendered headlessly.
+struct World {
+    bounds: Bounds,
+    player: Player,
+    asteroids: Vec<Asteroid>,
+    particles: Vec<Particle>,
+    popups: Vec<ScorePopup>,
+    shockwaves: Vec<Shockwave>,
+    /// `(x, y, brightness)` of each background star, with the position as
+    /// a fraction of the field so the stars follow it through resizes.
+    starfield: Vec<(f64, f64, f64)>,
+    /// How far the starfield has scrolled against the ship's motion, in
+    /// pixels.
+    star_drift: (f64, f64),
+    /// Asteroids of the starting wave still waiting for their turn to
+    /// appear, in order.
+    pending_spawns: VecDeque<Asteroid>,
+    /// Seconds until the next pending asteroid appears.
+    spawn_timer: f64,
+    waves: Vec<WaveSpec>,
+    /// The wave in progress, counting from 1.
+    wave: u32,
+    wave_stats: WaveStats,
+    /// Counters summed over every wave already cleared.
+    played: WaveStats,
+    /// Multiplier on new waves' asteroid count and speed, moved after each
+    /// wave when adaptive difficulty is on.
+    difficulty: f64,
+    bullets: Vec<Bullet>,
+    ufos: Vec<Ufo>,
+    /// Seconds until the next saucer flies in.
+    ufo_timer: f64,
+    score: u32,
+    lives: u32,
+    /// Left of the current life when collision damage is on.
+    health: f64,
+    /// Share of a full tank left; thrust cuts out at zero.
+    fuel: f64,
+    game_over: bool,
+    /// Seconds left on the magnet, which draws small asteroids in to be
+    /// collected rather than dodged.
+    magnet_time: f64,
+    /// Multiplier on the points for each kill, raised by quick successions
+    /// of kills.
+    combo: u32,
+    /// Seconds left for another kill to raise the combo.
+    combo_timer: f64,
+    /// Seconds until the ship may fire again.
+    shot_cooldown: f64,
+    /// Seconds until the ship may jump to hyperspace again.
+    hyperspace_cooldown: f64,
+    input: HeldInput,
+    /// Length of the last update. Thrust and turn input, which arrive
+    /// between updates, act for this long.
+    input_time: f64,
+    /// Where the ship last was with no asteroid within `SAFE_DISTANCE`.
+    last_safe_position: (f64, f64),
+    continues: u32,
+    theme: Theme,
+    render_style: RenderStyle,
+    /// Canvas backing-store pixels per CSS pixel. Gameplay stays in CSS
+    /// pixels; only drawing is scaled.
+    pixel_ratio: f64,
+    /// The part of the canvas this game draws into, as `(x, y, w, h)` in
+    /// CSS pixels. `None` uses the whole play area.
+    viewport: Option<(f64, f64, f64, f64)>,
+    /// The size of the visible window onto a play field larger than the
+    /// canvas. The camera keeps the ship in its middle. `None` shows the
+    /// whole field.
+    view: Option<Bounds>,
+    /// Drawn over the frozen frame while paused, if set.
+    pause_overlay: Option<PauseOverlay>,
+    /// Update and render durations, collected only while profiling is on.
+    timings: Option<FrameTimings>,
+    /// Hand every this many drawn frames to the capture callback; 0 when
+    /// nothing is capturing.
+    capture_interval: u32,
+    /// Frames drawn since capturing started.
+    frame_index: u64,
+    config: GameConfig,
+    events: VecDeque<GameEvent>,
+    /// Spawns and despawns since the host last collected them. Only kept
+    /// while `track_lifecycle` is set, so nothing piles up unread.
+    lifecycle: Vec<LifecycleEvent>,
+    track_lifecycle: bool,
+    /// Sound effects due since the host last played them. Only kept while
+    /// `sound` is set.
+    sound_cues: Vec<SoundCue>,
+    sound: bool,
+    history: VecDeque<GameState>,
+    paused: bool,
+    context_lost: bool,
+    /// Drives every random choice the simulation makes, so a seed fixes the
+    /// whole run.
+    rng: Pcg32,
+    /// The seed `rng` started from.
+    seed: u64,
+    /// Id handed to the next asteroid that enters the field.
+    next_asteroid_id: u32,
+    /// Asteroid ids to blow up, one per frame, for scripted sequences.
+    destruction_queue: VecDeque<u32>,
+}
+
+/// `STAR_COUNT` stars at random spots, each as `(x, y, brightness)` with
+/// everything in `0..1`.
+fn starfield(seed: u64) -> Vec<(f64, f64, f64)> {
+    let mut rng = Pcg32::new(seed, STARFIELD_STREAM);
+    (0..STAR_COUNT)
+        .map(|_| (rng.gen(), rng.gen(), rng.gen_range(0.2..1.0)))
+        .collect()
+}
+
+impl World {
+    fn new(bounds: Bounds) -> World {
+        World::seeded(bounds, rand::random())
+    }
+
+    /// A world whose random choices all follow from `seed`.
+    fn seeded(bounds: Bounds, seed: u64) -> World {
+        let mut world = World::empty(bounds, seed);
+        world.spawn_wave(wave::spec_for(&world.waves, 0));
+        world
+    }
+
+    /// A world laid out by a hand-authored level. The level's asteroids
+    /// form the opening wave, and its wave script takes over from wave 2.
+    fn from_level(bounds: Bounds, level: &Level) -> World {
+        let mut world = World::empty(bounds, rand::random());
+        if let Some((x, y, heading)) = level.player {
+            world.player = Player::new(x, y);
+            world.player.angle = heading;
+        }
+        if !level.waves.is_empty() {
+            world.waves = level.waves.clone();
+        }
+        if level.asteroids.is_empty() {
+            world.spawn_wave(wave::spec_for(&world.waves, 0));
+            return world;
+        }
+        // Wave 1 is the hand-placed field, so the script starts at wave 2.
+        world.waves.insert(0, WaveSpec::default());
+        for placed in &level.asteroids {
+            let mut asteroid = Asteroid::with_size(placed.x, placed.y, placed.size, &mut world.rng);
+            if let Some((velocity_x, velocity_y)) = placed.velocity {
+                asteroid.velocity_x = velocity_x;
+                asteroid.velocity_y = velocity_y;
+            }
+            world.add_asteroid(asteroid);
+        }
+        world
+    }
+
+    /// Everything in its starting state, but with no asteroids yet.
+    fn empty(bounds: Bounds, seed: u64) -> World {
+        let player = Player::new(bounds.width / 2.0, bounds.height / 2.0);
+        let config = GameConfig::default();
+
+        World {
+            bounds,
+            player,
+            asteroids: Vec::new(),
+            particles: Vec::new(),
+            popups: Vec::new(),
+            shockwaves: Vec::new(),
+            starfield: starfield(seed),
+            star_drift: (0.0, 0.0),
+            pending_spawns: VecDeque::new(),
+            spawn_timer: 0.0,
+            waves: vec![WaveSpec::classic()],
+            wave: 1,
+            wave_stats: WaveStats::default(),
+            played: WaveStats::default(),
+            difficulty: 1.0,
+            bullets: Vec::new(),
+            ufos: Vec::new(),
+            ufo_timer: UFO_INTERVAL,
+            score: 0,
+            lives: STARTING_LIVES,
+            health: config.max_health,
+            fuel: 1.0,
+            game_over: false,
+            magnet_time: 0.0,
+            combo: 1,
+            combo_timer: 0.0,
+            shot_cooldown: 0.0,
+            hyperspace_cooldown: 0.0,
+            input: HeldInput::default(),
+            input_time: REFERENCE_FRAME,
+            last_safe_position: (bounds.width / 2.0, bounds.height / 2.0),
+            continues: 0,
+            theme: Theme::default(),
+            render_style: RenderStyle::default(),
+            pixel_ratio: 1.0,
+            viewport: None,
+            view: None,
+            pause_overlay: None,
+            timings: None,
+            capture_interval: 0,
+            frame_index: 0,
+            config,
+            events: VecDeque::new(),
+            lifecycle: Vec::new(),
+            track_lifecycle: false,
+            sound_cues: Vec::new(),
+            sound: false,
+            history: VecDeque::new(),
+            paused: false,
+            context_lost: false,
+            rng: Pcg32::seed_from_u64(seed),
+            seed,
+            next_asteroid_id: 0,
+            destruction_queue: VecDeque::new(),
+        }
+    }
+
+    /// Adds the asteroids described by `spec` at random positions, or along
+    /// the edges if the spec asks for it. With a wave intro interval set,
+    /// the first appears now and the rest follow one at a time.
+    fn spawn_wave(&mut self, spec: WaveSpec) {
+        let speedup = 1.0 + self.config.wave_speedup * self.wave.saturating_sub(1) as f64;
+        for (size, count) in spec.asteroids() {
+            let count = (count as f64 * self.difficulty).round() as u32;
+            for _ in 0..count {
+                let mut asteroid = self.spawn_candidate(size, spec.from_edges);
+                for _ in 0..SPAWN_ATTEMPTS {
+                    if !self.spawn_blocked(asteroid.x, asteroid.y) {
+                        break;
+                    }
+                    asteroid = self.spawn_candidate(size, spec.from_edges);
+                }
+                asteroid.velocity_x *= self.difficulty * speedup;
+                asteroid.velocity_y *= self.difficulty * speedup;
+                self.pending_spawns.push_back(asteroid);
+            }
+        }
+        if self.config.wave_intro_interval <= 0.0 {
+            while let Some(asteroid) = self.pending_spawns.pop_front() {
+                self.add_asteroid(asteroid);
+            }
+        } else {
+            self.spawn_timer = 0.0;
+            self.release_spawns(0.0);
+        }
+    }
+
+    fn spawn_candidate(&mut self, size: f64, from_edges: bool) -> Asteroid {
+        if from_edges {
+            self.edge_asteroid(size)
+        } else {
+            let x = self.rng.gen::<f64>() * self.bounds.width;
+            let y = self.rng.gen::<f64>() * self.bounds.height;
+            Asteroid::with_size(x, y, size, &mut self.rng)
+        }
+    }
+
+    /// Whether a new asteroid at `(x, y)` would land somewhere the player is
+    /// protected from: right on top of the ship, or in the cone ahead of it.
+    fn spawn_blocked(&self, x: f64, y: f64) -> bool {
+        let (dx, dy) = toroidal_delta((x, y), (self.player.x, self.player.y), self.bounds);
+        if dx.hypot(dy) < SPAWN_CLEARANCE {
+            return true;
+        }
+        let cone = self.config.spawn_safe_cone;
+        if cone <= 0.0 {
+            return false;
+        }
+        if dx == 0.0 && dy == 0.0 {
+            return true;
+        }
+        // Heading 0 points up the screen.
+        let heading = self.player.angle - PI / 2.0;
+        let apart = (dy.atan2(dx) - heading + PI).rem_euclid(TAU) - PI;
+        apart.abs() <= cone
+    }
+
+    /// Counts down the wave intro by `dt` seconds, bringing in the next
+    /// queued asteroid each time the interval elapses.
+    fn release_spawns(&mut self, dt: f64) {
+        if self.pending_spawns.is_empty() {
+            return;
+        }
+        self.spawn_timer -= dt;
+        while self.spawn_timer <= 0.0 {
+            let Some(asteroid) = self.pending_spawns.pop_front() else {
+                break;
+            };
+            self.add_asteroid(asteroid);
+            self.spawn_timer += self.config.wave_intro_interval.max(REFERENCE_FRAME);
+        }
+    }
+
+    /// An asteroid on a random edge, heading inward within
+    /// `edge_spawn_spread` of straight in at `edge_entry_speed`.
+    fn edge_asteroid(&mut self, size: f64) -> Asteroid {
+        let Bounds { width, height } = self.bounds;
+        let inset = self.config.edge_spawn_offset;
+        let along = self.rng.gen::<f64>();
+        let (x, y, inward) = match self.rng.gen_range(0..4) {
+            0 => (inset, along * height, 0.0),
+            1 => (width - inset, along * height, PI),
+            2 => (along * width, inset, PI / 2.0),
+            _ => (along * width, height - inset, -PI / 2.0),
+        };
+        let spread = self.config.edge_spawn_spread;
+        let heading = inward + self.rng.gen_range(-1.0..=1.0) * spread;
+        let speed = self.config.edge_entry_speed;
+
+        let mut asteroid = Asteroid::with_size(x, y, size, &mut self.rng);
+        asteroid.velocity_x = heading.cos() * speed;
+        asteroid.velocity_y = heading.sin() * speed;
+        asteroid
+    }
+
+    /// The random generator's exact position as JSON, to resume the same
+    /// stream of randomness later.
+    fn rng_state(&self) -> String {
+        serde_json::to_string(&self.rng).unwrap_or_default()
+    }
+
+    /// Restores a state from `rng_state`. Invalid JSON leaves the generator
+    /// as it was.
+    fn set_rng_state(&mut self, json: &str) -> Result<(), String> {
+        self.rng = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Logs `event` for the host, dropping one per the overflow policy if
+    /// the log is full.
+    fn push_event(&mut self, event: GameEvent) {
+        let cap = self.config.event_log_cap;
+        if cap == 0 {
+            return;
+        }
+        if self.events.len() >= cap {
+            if self.config.event_overflow == EventOverflow::DropNewest {
+                return;
+            }
+            while self.events.len() >= cap {
+                self.events.pop_front();
+            }
+        }
+        self.events.push_back(event);
+    }
+
+    /// Gives `asteroid` the next free id and adds it to the field.
+    fn add_asteroid(&mut self, mut asteroid: Asteroid) {
+        asteroid.id = self.next_asteroid_id;
+        self.next_asteroid_id += 1;
+        self.track(Lifecycle::Spawned, &asteroid);
+        self.asteroids.push(asteroid);
+    }
+
+    /// Queues a sound effect for the host, if sound is on.
+    fn cue(&mut self, cue: SoundCue) {
+        if self.sound {
+            self.sound_cues.push(cue);
+        }
+    }
+
+    /// Queues a lifecycle change for the host, if it is listening.
+    fn track(&mut self, change: Lifecycle, asteroid: &Asteroid) {
+        if self.track_lifecycle {
+            self.lifecycle.push(LifecycleEvent::asteroid(change, asteroid));
+        }
+    }
+
+    fn snapshot(&self) -> GameState {
+        GameState {
+            player: self.player.clone(),
+            asteroids: self.asteroids.clone(),
+            bullets: self.bullets.clone(),
+            ufos: self.ufos.clone(),
+            wave: self.wave,
+            wave_stats: self.wave_stats,
+            played: self.played,
+            score: self.score,
+            lives: self.lives,
+            health: self.health,
+            fuel: self.fuel,
+            game_over: self.game_over,
+            magnet_time: self.magnet_time,
+            combo: self.combo,
+            combo_timer: self.combo_timer,
+            difficulty: self.difficulty,
+        }
+    }
+
+    fn restore(&mut self, state: GameState) {
+        self.player = state.player;
+        self.asteroids = state.asteroids;
+        self.bullets = state.bullets;
+        self.ufos = state.ufos;
+        self.wave = state.wave;
+        self.wave_stats = state.wave_stats;
+        self.played = state.played;
+        self.score = state.score;
+        self.lives = state.lives;
+        self.health = state.health;
+        self.fuel = state.fuel;
+        self.game_over = state.game_over;
+        self.magnet_time = state.magnet_time;
+        self.combo = state.combo;
+        self.combo_timer = state.combo_timer;
+        self.difficulty = state.difficulty;
+        self.refresh_background();
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(&self.snapshot()).unwrap_or_default()
+    }
+
+    /// Replaces the game in progress with a saved one. Rewind history and
+    /// asteroids still waiting to enter belong to the old game and are
+    /// dropped.
+    fn load_json(&mut self, json: &str) -> Result<(), String> {
+        let state: GameState = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let next_id = state.asteroids.iter().map(|a| a.id + 1).max().unwrap_or(0);
+        self.restore(state);
+        self.next_asteroid_id = self.next_asteroid_id.max(next_id);
+        self.history.clear();
+        self.pending_spawns.clear();
+        self.destruction_queue.clear();
+        Ok(())
+    }
+
+    /// Points the theme's background at the current wave's color.
+    fn refresh_background(&mut self) {
+        if !self.theme.wave_backgrounds.is_empty() {
+            self.theme.background = self.theme.background_for_wave(self.wave);
+        }
+    }
+
+    /// Steps back `steps` updates, or as far as the history reaches. Returns
+    /// how many steps were actually undone.
+    fn rewind(&mut self, steps: usize) -> usize {
+        let steps = steps.min(self.history.len());
+        if steps == 0 {
+            return 0;
+        }
+        self.history.truncate(self.history.len() - steps + 1);
+        if let Some(state) = self.history.pop_back() {
+            self.restore(state);
+        }
+        steps
+    }
+
+    /// How threatened the ship is, from 0 (clear skies) to 1, weighting each
+    /// asteroid by how close it is.
+    fn danger_level(&self) -> f64 {
+        let ship = (self.player.x, self.player.y);
+        let weight: f64 = self
+            .asteroids
+            .iter()
+            .map(|a| {
+                let distance = toroidal_distance(ship, (a.x, a.y), self.bounds);
+                (1.0 - distance / DANGER_RADIUS).max(0.0)
+            })
+            .sum();
+        (weight / DANGER_SATURATION).min(1.0)
+    }
+
+    /// Every entity whose position or velocity is not finite, described by
+    /// kind and index, or by id for asteroids.
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let p = &self.player;
+        let mut entities = vec![("player".to_string(), (p.x, p.y), (p.velocity_x, p.velocity_y))];
+        entities.extend(self.asteroids.iter().map(|a| {
+            (format!("asteroid {}", a.id), (a.x, a.y), (a.velocity_x, a.velocity_y))
+        }));
+        entities.extend(self.bullets.iter().enumerate().map(|(i, b)| {
+            (format!("bullet {i}"), (b.x, b.y), (b.velocity_x, b.velocity_y))
+        }));
+        entities.extend(self.ufos.iter().enumerate().map(|(i, u)| {
+            (format!("ufo {i}"), (u.x, u.y), (u.velocity_x, u.velocity_y))
+        }));
+        entities.extend(self.particles.iter().enumerate().map(|(i, p)| {
+            (format!("particle {i}"), (p.x, p.y), (p.velocity_x, p.velocity_y))
+        }));
+
+        let finite = |(x, y): (f64, f64)| x.is_finite() && y.is_finite();
+        let mut problems = Vec::new();
+        for (name, position, velocity) in entities {
+            if !finite(position) {
+                problems.push(format!("{name} position is {position:?}"));
+            }
+            if !finite(velocity) {
+                problems.push(format!("{name} velocity is {velocity:?}"));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Changes the play area, clamping everything into it so nothing is
+    /// stranded beyond the new edges. Wrapping follows the new size from
+    /// the next update.
+    fn resize(&mut self, bounds: Bounds) {
+        self.bounds = bounds;
+        let clamp = |x: &mut f64, y: &mut f64| {
+            *x = x.clamp(0.0, bounds.width);
+            *y = y.clamp(0.0, bounds.height);
+        };
+        clamp(&mut self.player.x, &mut self.player.y);
+        let safe = &mut self.last_safe_position;
+        clamp(&mut safe.0, &mut safe.1);
+        for asteroid in self.asteroids.iter_mut().chain(self.pending_spawns.iter_mut()) {
+            clamp(&mut asteroid.x, &mut asteroid.y);
+        }
+        for bullet in &mut self.bullets {
+            clamp(&mut bullet.x, &mut bullet.y);
+        }
+        for ufo in &mut self.ufos {
+            clamp(&mut ufo.x, &mut ufo.y);
+        }
+        for particle in &mut self.particles {
+            clamp(&mut particle.x, &mut particle.y);
+        }
+    }
+
+    /// The axis-aligned box, as `(x, y, w, h)`, that encloses every
+    /// asteroid and the ship out to their radii. Positions are taken as they
+    /// are, without looking across the wrap seams.
+    fn field_bounds(&self) -> (f64, f64, f64, f64) {
+        if self.asteroids.is_empty() {
+            return (0.0, 0.0, self.bounds.width, self.bounds.height);
+        }
+        let circles = self
+            .asteroids
+            .iter()
+            .map(|a| (a.x, a.y, a.size))
+            .chain([(self.player.x, self.player.y, SHIP_RADIUS)]);
+        let (mut left, mut top) = (f64::INFINITY, f64::INFINITY);
+        let (mut right, mut bottom) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for (x, y, radius) in circles {
+            left = left.min(x - radius);
+            top = top.min(y - radius);
+            right = right.max(x + radius);
+            bottom = bottom.max(y + radius);
+        }
+        (left, top, right - left, bottom - top)
+    }
+
+    /// Every entity overlapping the rectangle at `(x, y)` sized `w` by `h`.
+    fn entities_in_rect(&self, rect: (f64, f64, f64, f64)) -> Vec<EntityRef> {
+        let player = EntityRef {
+            kind: EntityKind::Player,
+            index: 0,
+            id: None,
+            x: self.player.x,
+            y: self.player.y,
+            radius: SHIP_RADIUS,
+            velocity_x: self.player.velocity_x,
+            velocity_y: self.player.velocity_y,
+        };
+        let asteroids = self.asteroids.iter().enumerate().map(|(index, a)| EntityRef {
+            kind: EntityKind::Asteroid,
+            index,
+            id: Some(a.id),
+            x: a.x,
+            y: a.y,
+            radius: a.size,
+            velocity_x: a.velocity_x,
+            velocity_y: a.velocity_y,
+        });
+        let bullets = self.bullets.iter().enumerate().map(|(index, b)| EntityRef {
+            kind: EntityKind::Bullet,
+            index,
+            id: None,
+            x: b.x,
+            y: b.y,
+            radius: BULLET_RADIUS,
+            velocity_x: b.velocity_x,
+            velocity_y: b.velocity_y,
+        });
+        std::iter::once(player)
+            .chain(asteroids)
+            .chain(bullets)
+            .filter(|e| circle_in_rect((e.x, e.y, e.radius), rect, self.bounds))
+            .collect()
+    }
+
+    /// Resumes a finished game with fresh lives at the cost of part of the
+    /// score. The asteroid field is left as it was.
+    fn continue_game(&mut self) {
+        if !self.game_over {
+            return;
+        }
+        self.lives = self.config.continue_lives;
+        self.health = self.config.max_health;
+        self.fuel = 1.0;
+        self.score = (self.score as f64 * self.config.continue_score_factor) as u32;
+        self.continues += 1;
+        self.game_over = false;
+        self.respawn_player();
+    }
+
+    /// Replaces the run with a fresh one that keeps the host's settings.
+    /// The new run is seeded from this one, so seeded games restart the same
+    /// way every time.
+    fn restart(&mut self) {
+        let mut fresh = World::empty(self.bounds, self.rng.gen());
+        fresh.config = self.config.clone();
+        fresh.health = fresh.config.max_health;
+        fresh.waves = std::mem::take(&mut self.waves);
+        fresh.theme = std::mem::take(&mut self.theme);
+        fresh.render_style = self.render_style;
+        fresh.pixel_ratio = self.pixel_ratio;
+        fresh.viewport = self.viewport;
+        fresh.view = self.view;
+        fresh.pause_overlay = self.pause_overlay.take();
+        fresh.timings = self.timings.take();
+        fresh.context_lost = self.context_lost;
+        fresh.paused = self.context_lost;
+        fresh.events = std::mem::take(&mut self.events);
+        for asteroid in std::mem::take(&mut self.asteroids) {
+            self.track(Lifecycle::Despawned, &asteroid);
+        }
+        fresh.lifecycle = std::mem::take(&mut self.lifecycle);
+        fresh.track_lifecycle = self.track_lifecycle;
+        fresh.sound = self.sound;
+        fresh.spawn_wave(wave::spec_for(&fresh.waves, 0));
+        fresh.refresh_background();
+        *self = fresh;
+    }
+
+    /// Where the ship should reappear under the configured strategy.
+    fn respawn_point(&self) -> (f64, f64) {
+        match self.config.respawn_strategy {
+            RespawnStrategy::Center => (self.bounds.width / 2.0, self.bounds.height / 2.0),
+            RespawnStrategy::SafestPoint => self.safest_point(),
+            RespawnStrategy::LastSafePosition => self.last_safe_position,
+        }
+    }
+
+    /// Distance from `point` to the surface of the nearest asteroid.
+    fn clearance(&self, point: (f64, f64)) -> f64 {
+        self.asteroids
+            .iter()
+            .map(|a| toroidal_distance(point, (a.x, a.y), self.bounds) - a.size)
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// The point on a grid over the field with the most clearance from
+    /// every asteroid.
+    fn safest_point(&self) -> (f64, f64) {
+        let cell_width = self.bounds.width / RESPAWN_GRID as f64;
+        let cell_height = self.bounds.height / RESPAWN_GRID as f64;
+        let mut best = (self.bounds.width / 2.0, self.bounds.height / 2.0);
+        let mut best_clearance = self.clearance(best);
+        for column in 0..RESPAWN_GRID {
+            for row in 0..RESPAWN_GRID {
+                let point = (
+                    (column as f64 + 0.5) * cell_width,
+                    (row as f64 + 0.5) * cell_height,
+                );
+                let clearance = self.clearance(point);
+                if clearance > best_clearance {
+                    best = point;
+                    best_clearance = clearance;
+                }
+            }
+        }
+        best
+    }
+
+    /// Advances everything by `dt` seconds.
+    fn update(&mut self, dt: f64) -> FrameSummary {
+        if self.paused {
+            return self.summary();
+        }
+        let dt = if dt.is_finite() { dt.clamp(0.0, MAX_STEP) } else { 0.0 };
+        if dt > 0.0 {
+            self.input_time = dt;
+        }
+        let bounds = self.bounds;
+        if self.history.len() == REWIND_CAPACITY {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.snapshot());
+
+        if dt > 0.0 {
+            self.apply_held_input();
+        }
+        if !self.player.thrusting {
+            self.fuel = (self.fuel + self.config.fuel_regen_rate * dt).min(1.0);
+        }
+        self.player.update(bounds, &self.config, dt);
+        let parallax = STARFIELD_PARALLAX * dt;
+        self.star_drift = (
+            (self.star_drift.0 - self.player.velocity_x * parallax).rem_euclid(bounds.width),
+            (self.star_drift.1 - self.player.velocity_y * parallax).rem_euclid(bounds.height),
+        );
+        if self.clearance((self.player.x, self.player.y)) > SAFE_DISTANCE {
+            self.last_safe_position = (self.player.x, self.player.y);
+        }
+        if self.magnet_time > 0.0 {
+            self.attract_asteroids(dt);
+        }
+        
+        // Update bullets
+        for bullet in &mut self.bullets {
+            bullet.update(bounds, &self.config, dt);
+        }
+        
+        // Update asteroids
+        for asteroid in &mut self.asteroids {
+            asteroid.update(bounds, &self.config, dt);
+        }
+
+        self.update_ufos(dt);
+
+        self.release_spawns(dt);
+
+        // Update particles
+        if self.config.particles_enabled {
+            for particle in &mut self.particles {
+                particle.update(dt);
+            }
+            self.particles.retain(|particle| !particle.is_expired());
+        } else {
+            self.particles.clear();
+        }
+
+        for popup in &mut self.popups {
+            popup.update(dt);
+        }
+        self.popups.retain(|popup| !popup.is_expired());
+
+        for shockwave in &mut self.shockwaves {
+            shockwave.update(dt);
+        }
+        self.shockwaves.retain(|shockwave| !shockwave.is_expired());
+
+        self.bullets.retain(|bullet| !bullet.is_expired());
+
+        // Check collisions
+        self.check_collisions();
+        self.destroy_next_queued();
+
+        self.magnet_time = (self.magnet_time - dt).max(0.0);
+        self.combo_timer = (self.combo_timer - dt).max(0.0);
+        if self.combo_timer == 0.0 {
+            self.combo = 1;
+        }
+        self.shot_cooldown = (self.shot_cooldown - dt).max(0.0);
+        self.hyperspace_cooldown = (self.hyperspace_cooldown - dt).max(0.0);
+        if !self.game_over {
+            self.wave_stats.elapsed += dt;
+        }
+        if self.asteroids.is_empty() && self.pending_spawns.is_empty() {
+            self.clear_wave();
+        }
+
+        self.summary()
+    }
+
+    /// Flies in a saucer every `UFO_INTERVAL` while none is on screen,
+    /// moves the ones present and lets each fire at the ship when its timer
+    /// runs out. Saucers that have crossed the field leave it.
+    fn update_ufos(&mut self, dt: f64) {
+        if !self.config.ufos_enabled {
+            self.ufos.clear();
+            return;
+        }
+        if self.ufos.is_empty() && !self.game_over {
+            self.ufo_timer -= dt;
+            if self.ufo_timer <= 0.0 {
+                self.ufo_timer = UFO_INTERVAL;
+                let ufo = Ufo::spawn(self.bounds, &mut self.rng);
+                self.ufos.push(ufo);
+            }
+        }
+        let target = (self.player.x, self.player.y);
+        for ufo in &mut self.ufos {
+            ufo.update(self.bounds, dt);
+            if ufo.fire_timer <= 0.0 && !self.game_over {
+                ufo.fire_timer = UFO_FIRE_INTERVAL;
+                self.bullets.push(ufo.fire_at(target, self.bounds));
+            }
+        }
+        let bounds = self.bounds;
+        self.ufos.retain(|ufo| !ufo.has_crossed(bounds));
+    }
+
+    fn apply_held_input(&mut self) {
+        let input = self.input;
+        if input.left {
+            self.turn(-1.0);
+        }
+        if input.right {
+            self.turn(1.0);
+        }
+        if input.thrust {
+            self.thrust();
+        }
+        if input.shoot {
+            self.shoot();
+        }
+    }
+
+    /// Applies the inputs packed into `input` and then advances `dt`
+    /// seconds, so fuzzers and property tests can drive whole games from a
+    /// byte stream. Bit 0 turns left, bit 1 right, bit 2 thrusts, bit 3
+    /// shoots and bit 4 jumps to hyperspace; the rest are ignored.
+    #[cfg(any(test, feature = "native"))]
+    fn fuzz_step(&mut self, input: u8, dt: f64) -> FrameSummary {
+        if input & 0b0001 != 0 {
+            self.turn(-1.0);
+        }
+        if input & 0b0010 != 0 {
+            self.turn(1.0);
+        }
+        if input & 0b0100 != 0 {
+            self.thrust();
+        }
+        if input & 0b1000 != 0 {
+            self.shoot();
+        }
+        if input & 0b1_0000 != 0 {
+            self.hyperspace();
+        }
+        self.update(dt)
+    }
+
+    /// Updates `dt` at a time until `predicate` holds or `max_steps` updates
+    /// have run, returning whether the condition was reached. The predicate
+    /// is checked before the first step, so an already-true condition costs
+    /// no updates.
+    #[cfg(test)]
+    fn run_until(
+        &mut self,
+        dt: f64,
+        max_steps: u32,
+        predicate: impl Fn(&World) -> bool,
+    ) -> bool {
+        for _ in 0..max_steps {
+            if predicate(self) {
+                return true;
+            }
+            self.update(dt);
+        }
+        predicate(self)
+    }
+
+    /// Awards the bonus for the finished wave and spawns the next one.
+    fn clear_wave(&mut self) {
+        let (time_bonus, accuracy_bonus) = self.wave_stats.clear_bonus();
+        self.score += time_bonus + accuracy_bonus;
+        self.push_event(GameEvent::WaveCleared {
+            wave: self.wave,
+            time_bonus,
+            accuracy_bonus,
+        });
+        if self.config.adaptive_difficulty {
+            self.difficulty = (self.difficulty + self.wave_stats.difficulty_adjustment())
+                .clamp(MIN_DIFFICULTY, MAX_DIFFICULTY);
+        }
+        self.played.accumulate(&self.wave_stats);
+        self.wave_stats = WaveStats::default();
+        self.wave += 1;
+        self.refresh_background();
+        self.spawn_wave(wave::spec_for(&self.waves, self.wave as usize - 1));
+    }
+
+    /// The run's outcome for a leaderboard, counting the wave in progress.
+    fn score_summary(&self) -> ScoreSummary {
+        let mut played = self.played;
+        played.accumulate(&self.wave_stats);
+        ScoreSummary {
+            score: self.score,
+            wave: self.wave,
+            accuracy: played.accuracy(),
+            time_survived: played.elapsed,
+            seed: self.seed,
+        }
+    }
+
+    fn summary(&self) -> FrameSummary {
+        FrameSummary {
+            score: self.score,
+            lives: self.lives,
+            wave: self.wave,
+            asteroids: self.asteroids.len(),
+        }
+    }
+
+    /// The canvas region drawn into, defaulting to everything visible.
+    fn viewport(&self) -> (f64, f64, f64, f64) {
+        let visible = self.view.unwrap_or(self.bounds);
+        self.viewport.unwrap_or((0.0, 0.0, visible.width, visible.height))
+    }
+
+    /// The play-field point shown at the top-left of the view.
+    fn camera_origin(&self) -> (f64, f64) {
+        match self.view {
+            Some(view) => (
+                self.player.x - view.width / 2.0,
+                self.player.y - view.height / 2.0,
+            ),
+            None => (0.0, 0.0),
+        }
+    }
+
+    /// How far to move an entity at `point` so it is drawn at whichever of
+    /// its wrapped copies lies nearest the camera.
+    fn image_shift(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        if self.view.is_none() {
+            return (0.0, 0.0);
+        }
+        let center = (self.player.x, self.player.y);
+        let (dx, dy) = toroidal_delta((x, y), center, self.bounds);
+        (center.0 + dx - x, center.1 + dy - y)
+    }
+
+    /// Where a point in play-field (CSS pixel) coordinates lands on the
+    /// canvas backing store.
+    #[cfg(test)]
+    fn to_device(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let (left, top, _, _) = self.viewport();
+        let (camera_x, camera_y) = self.camera_origin();
+        (
+            (x - camera_x + left) * self.pixel_ratio,
+            (y - camera_y + top) * self.pixel_ratio,
+        )
+    }
+
+    #[cfg(feature = "web")]
+    fn render(&self, surface: &impl Surface) -> Result<(), JsValue> {
+        // Clear the viewport
+        let (left, top, width, height) = self.viewport();
+        let ratio = self.pixel_ratio;
+        surface.clear_rect(left * ratio, top * ratio, width * ratio, height * ratio);
+        if let Some(background) = self.theme.background {
+            surface.begin_path();
+            surface.rect(left * ratio, top * ratio, width * ratio, height * ratio);
+            surface.set_fill_style(&background.css());
+            surface.fill();
+        }
+
+        surface.save();
+        surface.scale(self.pixel_ratio, self.pixel_ratio)?;
+        if self.viewport.is_some() {
+            surface.begin_path();
+            surface.rect(left, top, width, height);
+            surface.clip();
+            surface.translate(left, top)?;
+        }
+        if self.view.is_some() {
+            let (camera_x, camera_y) = self.camera_origin();
+            surface.translate(-camera_x, -camera_y)?;
+        }
+        let drawn = self.draw_entities(surface);
+        surface.restore();
+        drawn?;
+
+        if let Some(overlay) = self.pause_overlay.as_ref().filter(|_| self.paused) {
+            surface.save();
+            surface.scale(self.pixel_ratio, self.pixel_ratio)?;
+            overlay.draw(surface, (left, top, width, height), &self.theme.ship)?;
+            surface.restore();
+        }
+        Ok(())
+    }
+
+    /// Runs `draw` for an entity at `point`, moved to its copy nearest the
+    /// camera when the field is larger than the view.
+    #[cfg(feature = "web")]
+    fn draw_near_camera(
+        &self,
+        surface: &impl Surface,
+        point: (f64, f64),
+        draw: impl Fn() -> Result<(), JsValue>,
+    ) -> Result<(), JsValue> {
+        let (dx, dy) = self.image_shift(point);
+        if (dx, dy) == (0.0, 0.0) {
+            return draw();
+        }
+        surface.save();
+        surface.translate(dx, dy)?;
+        draw()?;
+        surface.restore();
+        Ok(())
+    }
+
+    /// Where each star sits in the field right now, with its brightness.
+    fn stars(&self) -> impl Iterator<Item = (f64, f64, f64)> + '_ {
+        let (width, height) = (self.bounds.width, self.bounds.height);
+        let (drift_x, drift_y) = self.star_drift;
+        self.starfield.iter().map(move |&(x, y, brightness)| {
+            (
+                (x * width + drift_x).rem_euclid(width),
+                (y * height + drift_y).rem_euclid(height),
+                brightness,
+            )
+        })
+    }
+
+    /// Draws everything in play-field coordinates.
+    #[cfg(feature = "web")]
+    fn draw_entities(&self, surface: &impl Surface) -> Result<(), JsValue> {
+        // Draw the starfield behind everything else
+        for (x, y, brightness) in self.stars() {
+            surface.begin_path();
+            surface.rect(x, y, 1.5, 1.5);
+            surface.set_fill_style(&Rgba::new(255, 255, 255, brightness * STAR_MAX_ALPHA).css());
+            surface.fill();
+        }
+
+        // Draw player
+        let player = &self.player;
+        let flame = theme::gradient_at(&self.theme.thrust_gradient, 0.5).css();
+        draw_wrapped(surface, (player.x, player.y, SHIP_RADIUS), self.bounds, || {
+            player.draw(surface, &self.theme.ship, &flame, self.render_style)
+        })?;
+        
+        // Draw asteroids
+        for asteroid in &self.asteroids {
+            self.draw_near_camera(surface, (asteroid.x, asteroid.y), || {
+                draw_wrapped(surface, (asteroid.x, asteroid.y, asteroid.size), self.bounds, || {
+                    if self.config.asteroid_trails {
+                        asteroid.draw_trail(surface, &self.theme.asteroid, &self.config);
+                    }
+                    asteroid.draw(surface, &self.theme.asteroid, self.render_style)
+                })
+            })?;
+        }
+        
+        // Draw saucers
+        for ufo in &self.ufos {
+            self.draw_near_camera(surface, (ufo.x, ufo.y), || ufo.draw(surface, &self.theme.ufo))?;
+        }
+
+        // Draw bullets
+        for bullet in &self.bullets {
+            let color = match bullet.owner {
+                BulletOwner::Player => &self.theme.bullet,
+                BulletOwner::Enemy => &self.theme.ufo,
+            };
+            self.draw_near_camera(surface, (bullet.x, bullet.y), || bullet.draw(surface, color))?;
+        }
+
+        // Draw particles (none survive an update while they are disabled)
+        for particle in &self.particles {
+            self.draw_near_camera(surface, (particle.x, particle.y), || {
+                particle.draw(surface, &self.theme.thrust_gradient, &self.theme.asteroid)
+            })?;
+        }
+
+        // Draw shockwaves
+        for shockwave in &self.shockwaves {
+            self.draw_near_camera(surface, (shockwave.x, shockwave.y), || {
+                shockwave.draw(surface, &self.theme.asteroid)
+            })?;
+        }
+
+        // Draw score popups
+        for popup in &self.popups {
+            self.draw_near_camera(surface, (popup.x, popup.y), || {
+                popup.draw(surface, &self.theme.ship)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "web")]
+    fn tick(&mut self, surface: &impl Surface, dt: f64) -> FrameSummary {
+        if self.timings.is_none() {
+            let summary = self.update(dt);
+            self.present(surface);
+            return summary;
+        }
+        let start = timing::now_ms();
+        let summary = self.update(dt);
+        let updated = timing::now_ms();
+        self.present(surface);
+        let rendered = timing::now_ms();
+        if let Some(timings) = &mut self.timings {
+            timings.record(updated - start, rendered - updated);
+        }
+        summary
+    }
+
+    /// Counts a drawn frame, returning its index if it is one the capture
+    /// cadence wants.
+    fn advance_capture(&mut self) -> Option<u64> {
+        if self.capture_interval == 0 {
+            return None;
+        }
+        let index = self.frame_index;
+        self.frame_index += 1;
+        index.is_multiple_of(self.capture_interval as u64).then_some(index)
+    }
+
+    /// Draws a fresh frame and encodes it as an image.
+    #[cfg(feature = "web")]
+    fn capture_frame(&self, surface: &impl Surface) -> Result<String, JsValue> {
+        if self.context_lost {
+            return Err(JsValue::from_str("canvas context is lost"));
+        }
+        self.render(surface)?;
+        surface.to_data_url()
+    }
+
+    /// Renders unless the surface has been lost, pausing the game if drawing
+    /// fails.
+    #[cfg(feature = "web")]
+    fn present(&mut self, surface: &impl Surface) {
+        if !self.context_lost && self.render(surface).is_err() {
+            self.lose_context();
+        }
+    }
+
+    fn lose_context(&mut self) {
+        if self.context_lost {
+            return;
+        }
+        self.context_lost = true;
+        self.paused = true;
+        self.push_event(GameEvent::ContextLost);
+    }
+
+    /// Freezes the simulation at the host's request.
+    fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Lifts a host pause. A game paused by a lost context stays paused
+    /// until `recover_context`.
+    fn resume(&mut self) {
+        self.paused = self.context_lost;
+    }
+
+    fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    fn recover_context(&mut self) {
+        self.context_lost = false;
+        self.paused = false;
+    }
+
+    /// Applies one frame of turn input, `-1` for left and `1` for right.
+    fn turn(&mut self, direction: f64) {
+        self.player.turn(direction, &self.config, self.input_time);
+    }
+
+    /// Switches the magnet on for `MAGNET_DURATION` seconds, or tops it back
+    /// up if it is already running.
+    fn activate_magnet(&mut self) {
+        if !self.config.power_ups_enabled {
+            return;
+        }
+        self.magnet_time = MAGNET_DURATION;
+    }
+
+    /// Small asteroids within range fall towards the ship.
+    fn attract_asteroids(&mut self, dt: f64) {
+        let ship = (self.player.x, self.player.y);
+        for asteroid in &mut self.asteroids {
+            if asteroid.size > SMALL_ASTEROID_SIZE || asteroid.is_intangible() {
+                continue;
+            }
+            let (dx, dy) = toroidal_delta(ship, (asteroid.x, asteroid.y), self.bounds);
+            let distance = dx.hypot(dy);
+            if distance > 0.0 && distance <= MAGNET_RANGE {
+                asteroid.velocity_x += dx / distance * MAGNET_PULL * dt;
+                asteroid.velocity_y += dy / distance * MAGNET_PULL * dt;
+            }
+        }
+    }
+
+    /// Asteroids touching the ship. While the magnet runs, small ones are
+    /// collected for a bonus.
+    fn check_ship_collisions(&mut self) {
+        if self.config.near_miss_scoring {
+            self.score_near_misses();
+        }
+        if self.magnet_time > 0.0 {
+            self.collect_magnetized();
+        }
+        if self.game_over || self.player.is_invulnerable() {
+            return;
+        }
+        if self.config.collision_damage {
+            self.take_collision_damage();
+        } else if self.ship_is_hit() {
+            self.lose_life();
+        }
+    }
+
+    /// Breaks up every tangible asteroid touching the ship and takes the
+    /// damage each deals out of the ship's health, losing a life if it runs
+    /// out. The fragments start with spawn grace so they cannot hit the ship
+    /// again on the way out.
+    fn take_collision_damage(&mut self) {
+        let player = &self.player;
+        let ship_velocity = (player.velocity_x, player.velocity_y);
+        let bounds = self.bounds;
+        let config = &self.config;
+        let mut damage = 0.0;
+        let mut fragments = Vec::new();
+        let mut hits = Vec::new();
+        self.asteroids.retain(|asteroid| {
+            let hit = !asteroid.is_intangible() && player.collides_with(asteroid, bounds);
+            if hit {
+                let closing = (asteroid.velocity_x - ship_velocity.0)
+                    .hypot(asteroid.velocity_y - ship_velocity.1);
+                damage += asteroid.size * config.damage_per_size
+                    + closing * config.damage_per_speed;
+                fragments.extend(asteroid.split(config, &mut self.rng));
+                hits.push(asteroid.clone());
+            }
+            !hit
+        });
+        for asteroid in &hits {
+            self.track(Lifecycle::Despawned, asteroid);
+        }
+        for mut fragment in fragments {
+            fragment.grace = ASTEROID_SPAWN_GRACE;
+            self.add_asteroid(fragment);
+        }
+        if damage == 0.0 {
+            return;
+        }
+        self.health -= damage;
+        if self.health <= 0.0 {
+            self.lose_life();
+        }
+    }
+
+    /// Whether a tangible asteroid touches the ship's hull.
+    fn ship_is_hit(&self) -> bool {
+        self.asteroids.iter().any(|asteroid| {
+            !asteroid.is_intangible() && self.player.collides_with(asteroid, self.bounds)
+        })
+    }
+
+    /// Costs a life and puts the ship back at rest at the respawn point, or
+    /// ends the game when none are left.
+    fn lose_life(&mut self) {
+        self.lives = self.lives.saturating_sub(1);
+        self.wave_stats.deaths += 1;
+        self.health = self.config.max_health;
+        self.fuel = 1.0;
+        if self.lives == 0 {
+            self.game_over = true;
+            return;
+        }
+        self.respawn_player();
+    }
+
+    /// Puts a fresh ship at the respawn point, briefly invulnerable so a
+    /// rock drifting over it cannot take the new life straight away.
+    fn respawn_player(&mut self) {
+        let (x, y) = self.respawn_point();
+        self.player = Player::new(x, y);
+        self.player.invulnerable = RESPAWN_INVULNERABILITY;
+    }
+
+    /// Scoops up small asteroids touching the ship while the magnet is on.
+    fn collect_magnetized(&mut self) {
+        let ship = (self.player.x, self.player.y);
+        let bounds = self.bounds;
+        let mut collected = Vec::new();
+        self.asteroids.retain(|asteroid| {
+            let touching = toroidal_distance(ship, (asteroid.x, asteroid.y), bounds)
+                < SHIP_RADIUS + asteroid.size;
+            let caught =
+                touching && asteroid.size <= SMALL_ASTEROID_SIZE && !asteroid.is_intangible();
+            if caught {
+                collected.push(asteroid.clone());
+            }
+            !caught
+        });
+        for asteroid in collected {
+            self.track(Lifecycle::Despawned, &asteroid);
+            self.score += MAGNET_BONUS;
+            self.popups.push(ScorePopup::new(asteroid.x, asteroid.y, MAGNET_BONUS));
+        }
+    }
+
+    /// Destroys the next queued asteroid that still exists, with its
+    /// explosion but without splitting it or scoring.
+    fn destroy_next_queued(&mut self) {
+        while let Some(id) = self.destruction_queue.pop_front() {
+            let Some(index) = self.asteroids.iter().position(|a| a.id == id) else {
+                continue;
+            };
+            let asteroid = self.asteroids.remove(index);
+            self.track(Lifecycle::Despawned, &asteroid);
+            self.shockwaves.push(Shockwave::new(asteroid.x, asteroid.y, asteroid.size));
+            self.explode(asteroid.x, asteroid.y);
+            self.cue(SoundCue::Explosion);
+            return;
+        }
+    }
+
+    /// Rewards each asteroid's first pass within the near-miss band around
+    /// the ship, just outside touching distance.
+    fn score_near_misses(&mut self) {
+        let ship = (self.player.x, self.player.y);
+        let band = self.config.near_miss_band;
+        let mut grazes = Vec::new();
+        for asteroid in &mut self.asteroids {
+            if asteroid.grazed || asteroid.is_intangible() {
+                continue;
+            }
+            let distance = toroidal_distance(ship, (asteroid.x, asteroid.y), self.bounds);
+            let touching = SHIP_RADIUS + asteroid.size;
+            if distance >= touching && distance < touching + band {
+                asteroid.grazed = true;
+                grazes.push((asteroid.x, asteroid.y));
+            }
+        }
+        for (x, y) in grazes {
+            self.score += NEAR_MISS_BONUS;
+            self.popups.push(ScorePopup::new(x, y, NEAR_MISS_BONUS));
+        }
+    }
+
+    /// Counts a kill towards the combo, returning the multiplier it earns.
+    /// A kill within `COMBO_WINDOW` of the last raises the combo by one, up
+    /// to `MAX_COMBO`; otherwise it starts over at 1.
+    fn extend_combo(&mut self) -> u32 {
+        self.combo = if self.combo_timer > 0.0 {
+            (self.combo + 1).min(MAX_COMBO)
+        } else {
+            1
+        };
+        self.combo_timer = COMBO_WINDOW;
+        self.combo
+    }
+
+    /// Throws `EXPLOSION_PARTICLES` debris particles out from `(x, y)`,
+    /// evenly spaced around the blast with a little jitter in direction and
+    /// speed.
+    fn explode(&mut self, x: f64, y: f64) {
+        if !self.config.particles_enabled {
+            return;
+        }
+        let step = TAU / EXPLOSION_PARTICLES as f64;
+        for i in 0..EXPLOSION_PARTICLES {
+            let angle = i as f64 * step + self.rng.gen_range(-0.25..0.25) * step;
+            let speed = EXPLOSION_SPEED * self.rng.gen_range(0.5..1.0);
+            self.particles.push(Particle::debris(x, y, angle, speed));
+        }
+    }
+
+    /// Thrusts for a frame, burning fuel, or does nothing on an empty tank.
+    fn thrust(&mut self) {
+        if self.config.fuel_burn_rate > 0.0 {
+            if self.fuel <= 0.0 {
+                return;
+            }
+            self.fuel = (self.fuel - self.config.fuel_burn_rate * self.input_time).max(0.0);
+        }
+        self.player.thrust(&self.config, self.input_time);
+        if self.config.particles_enabled {
+            let exhaust = self.player.exhaust(&mut self.rng);
+            self.particles.push(exhaust);
+        }
+    }
+
+    /// Moves the ship to a random point with its velocity cleared, keeping
+    /// its heading. Randomness comes from the world's generator, so seeded
+    /// games jump to the same places.
+    fn hyperspace(&mut self) {
+        if self.game_over || self.hyperspace_cooldown > 0.0 {
+            return;
+        }
+        self.hyperspace_cooldown = HYPERSPACE_COOLDOWN;
+        let player = &mut self.player;
+        player.x = self.rng.gen_range(0.0..self.bounds.width);
+        player.y = self.rng.gen_range(0.0..self.bounds.height);
+        (player.velocity_x, player.velocity_y) = (0.0, 0.0);
+        if self.rng.gen_bool(self.config.hyperspace_risk) {
+            self.lose_life();
+        }
+    }
+
+    /// Fires a shot unless the last one was under `FIRE_COOLDOWN` ago, so a
+    /// held fire button gives a steady stream rather than one bullet a frame.
+    fn shoot(&mut self) {
+        if self.shot_cooldown > 0.0 {
+            return;
+        }
+        let in_flight = self.bullets.iter().filter(|b| b.owner == BulletOwner::Player).count();
+        let room = self.config.max_bullets.saturating_sub(in_flight);
+        if room == 0 {
+            return;
+        }
+        self.fuel = (self.fuel - self.config.fuel_per_shot).max(0.0);
+        self.cue(SoundCue::Shoot);
+        let assist = self.aim_correction() * self.config.aim_assist;
+        for offset in pellet_offsets(self.config.pellets, self.config.spread_angle).take(room) {
+            let offset = offset + assist;
+            let bullet = self.player.shoot(offset);
+            self.wave_stats.shots_fired += 1;
+            self.push_event(GameEvent::BulletFired {
+                position: (bullet.x, bullet.y),
+                angle: self.player.angle + offset,
+            });
+            self.bullets.push(bullet);
+            self.shot_cooldown = FIRE_COOLDOWN;
+        }
+    }
+
+    /// Radians clockwise the nose would have to turn to point straight at
+    /// the nearest tangible asteroid inside the aim assist cone, or 0 if
+    /// there is none.
+    fn aim_correction(&self) -> f64 {
+        if self.config.aim_assist == 0.0 {
+            return 0.0;
+        }
+        let ship = (self.player.x, self.player.y);
+        self.asteroids
+            .iter()
+            .filter(|asteroid| !asteroid.is_intangible())
+            .filter_map(|asteroid| {
+                let (dx, dy) = toroidal_delta((asteroid.x, asteroid.y), ship, self.bounds);
+                let bearing = dx.atan2(-dy);
+                let turn = (bearing - self.player.angle + PI).rem_euclid(TAU) - PI;
+                (turn.abs() <= self.config.aim_assist_cone).then_some((dx.hypot(dy), turn))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or(0.0, |(_, turn)| turn)
+    }
+
+    fn check_collisions(&mut self) {
+        if self.config.bullet_interception {
+            self.intercept_bullets();
+        }
+        if self.config.asteroid_collisions {
+            self.bounce_asteroids();
+        }
+
+        // Check bullet-asteroid collisions. Every contact is gathered first
+        // and resolved in bullet order, then asteroid id order, so the same
+        // run always destroys the same rocks whatever order they are stored in.
+        let hits = self.bullet_hits();
+
+        let mut spent_bullets = vec![false; self.bullets.len()];
+        let mut deflected = vec![false; self.bullets.len()];
+        let mut destroyed = vec![false; self.asteroids.len()];
+        let mut fragments = Vec::new();
+        for (i, _, j) in hits {
+            if spent_bullets[i] || deflected[i] || destroyed[j] {
+                continue;
+            }
+            if self.config.weak_spot_mode
+                && !self.hits_weak_spot(&self.bullets[i], &self.asteroids[j])
+            {
+                deflected[i] = true;
+                let asteroid = self.asteroids[j].clone();
+                self.bullets[i].deflect_off(&asteroid, self.bounds);
+                continue;
+            }
+            spent_bullets[i] = true;
+            destroyed[j] = true;
+            let asteroid = &self.asteroids[j];
+            fragments.extend(asteroid.split(&self.config, &mut self.rng));
+            let (x, y, points) = (asteroid.x, asteroid.y, asteroid.points());
+            self.shockwaves.push(Shockwave::new(x, y, asteroid.size));
+            self.explode(x, y);
+            self.cue(SoundCue::Explosion);
+            let points = points * self.extend_combo();
+            self.popups.push(ScorePopup::new(x, y, points));
+            self.score += points;
+            self.wave_stats.shots_hit += 1;
+        }
+
+        let mut spent_bullets = spent_bullets.into_iter();
+        self.bullets.retain(|_| !spent_bullets.next().unwrap_or(false));
+        if self.track_lifecycle {
+            let gone = self.asteroids.iter().zip(&destroyed).filter(|(_, &d)| d);
+            self.lifecycle.extend(
+                gone.map(|(asteroid, _)| LifecycleEvent::asteroid(Lifecycle::Despawned, asteroid)),
+            );
+        }
+        let mut destroyed = destroyed.into_iter();
+        self.asteroids.retain(|_| !destroyed.next().unwrap_or(false));
+        for fragment in fragments {
+            self.add_asteroid(fragment);
+        }
+
+        self.check_ufo_hits();
+        self.check_ship_collisions();
+    }
+
+    /// Every `(bullet index, asteroid id, asteroid index)` where an armed
+    /// player bullet touches a tangible asteroid, sorted by bullet and then
+    /// asteroid id. Asteroids are bucketed on a grid first so each bullet is
+    /// only tested against the rocks around it.
+    fn bullet_hits(&self) -> Vec<(usize, u32, usize)> {
+        let mut grid = SpatialGrid::new(self.bounds.width, self.bounds.height);
+        for (j, asteroid) in self.asteroids.iter().enumerate() {
+            if !asteroid.is_intangible() {
+                grid.insert(j, (asteroid.x, asteroid.y), asteroid.size);
+            }
+        }
+        let mut hits = Vec::new();
+        for (i, bullet) in self.bullets.iter().enumerate() {
+            if bullet.owner != BulletOwner::Player || !bullet.is_armed(&self.config) {
+                continue;
+            }
+            for &j in grid.near((bullet.x, bullet.y)) {
+                let asteroid = &self.asteroids[j];
+                if bullet.collides_with(asteroid, self.bounds) {
+                    hits.push((i, asteroid.id, j));
+                }
+            }
+        }
+        hits.sort_unstable_by_key(|&(bullet, id, _)| (bullet, id));
+        hits
+    }
+
+    /// Destroys saucers struck by player bullets, for `UFO_POINTS` each, and
+    /// costs a life for every saucer shot that reaches the ship.
+    fn check_ufo_hits(&mut self) {
+        let bounds = self.bounds;
+        let mut spent = vec![false; self.bullets.len()];
+        let mut shot_down = Vec::new();
+        self.ufos.retain(|ufo| {
+            let hit = self.bullets.iter().enumerate().position(|(i, bullet)| {
+                !spent[i]
+                    && bullet.owner == BulletOwner::Player
+                    && bullet.is_armed(&self.config)
+                    && toroidal_distance((bullet.x, bullet.y), (ufo.x, ufo.y), bounds) < UFO_RADIUS
+            });
+            if let Some(i) = hit {
+                spent[i] = true;
+                shot_down.push((ufo.x, ufo.y));
+            }
+            hit.is_none()
+        });
+        for (x, y) in shot_down {
+            self.score += UFO_POINTS;
+            self.wave_stats.shots_hit += 1;
+            self.popups.push(ScorePopup::new(x, y, UFO_POINTS));
+            self.shockwaves.push(Shockwave::new(x, y, UFO_RADIUS));
+            self.cue(SoundCue::Explosion);
+        }
+
+        let mut ship_hit = false;
+        for (i, bullet) in self.bullets.iter().enumerate() {
+            if !self.player.is_invulnerable()
+                && bullet.owner == BulletOwner::Enemy
+                && bullet.is_armed(&self.config)
+                && self.player.touches_circle((bullet.x, bullet.y), BULLET_RADIUS, bounds)
+            {
+                spent[i] = true;
+                ship_hit = true;
+            }
+        }
+        if ship_hit && !self.game_over {
+            self.lose_life();
+        }
+        let mut spent = spent.into_iter();
+        self.bullets.retain(|_| !spent.next().unwrap_or(false));
+    }
+
+    /// Whether `bullet` struck `asteroid` within `weak_spot_arc` of the
+    /// side facing the ship.
+    fn hits_weak_spot(&self, bullet: &Bullet, asteroid: &Asteroid) -> bool {
+        let center = (asteroid.x, asteroid.y);
+        let (hit_x, hit_y) = toroidal_delta((bullet.x, bullet.y), center, self.bounds);
+        let (ship_x, ship_y) = toroidal_delta((self.player.x, self.player.y), center, self.bounds);
+        let hit = hit_y.atan2(hit_x);
+        let ship = ship_y.atan2(ship_x);
+        let apart = (hit - ship + PI).rem_euclid(TAU) - PI;
+        apart.abs() <= self.config.weak_spot_arc
+    }
+
+    /// Resolves asteroid-on-asteroid contacts as elastic collisions between
+    /// bodies of different mass, so big rocks shrug off small ones.
+    fn bounce_asteroids(&mut self) {
+        for i in 0..self.asteroids.len() {
+            let (left, right) = self.asteroids.split_at_mut(i + 1);
+            let a = &mut left[i];
+            for b in right.iter_mut() {
+                if !a.is_intangible() && !b.is_intangible() {
+                    a.bounce_off(b, self.bounds);
+                }
+            }
+        }
+    }
+
+    /// Destroys every player bullet that touches an enemy bullet, along with
+    /// the enemy bullet it hit.
+    fn intercept_bullets(&mut self) {
+        let mut destroyed = vec![false; self.bullets.len()];
+        for (i, shot) in self.bullets.iter().enumerate() {
+            if shot.owner != BulletOwner::Player || !shot.is_armed(&self.config) {
+                continue;
+            }
+            let target = self.bullets.iter().enumerate().position(|(j, other)| {
+                !destroyed[j] && other.owner == BulletOwner::Enemy && shot.collides_with_bullet(other, self.bounds)
+            });
+            if let Some(j) = target {
+                destroyed[i] = true;
+                destroyed[j] = true;
+            }
+        }
+        let mut destroyed = destroyed.into_iter();
+        self.bullets.retain(|_| !destroyed.next().unwrap_or(false));
+    }
+}
+
+/// Seconds a respawned ship is invulnerable for.
+const RESPAWN_INVULNERABILITY: f64 = 2.0;
+
+/// Seconds an invulnerable ship spends shown, then hidden, as it blinks.
+const INVULNERABLE_BLINK: f64 = 0.125;
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Player {
+    x: f64,
+    y: f64,
+    angle: f64,
+    velocity_x: f64,
+    velocity_y: f64,
+    /// Radians turned per second while turning inertia is on.
+    angular_velocity: f64,
+    /// Set by thrust input, and cleared as each update begins.
+    thrusting: bool,
+    /// Whether the last update ran under thrust, which draws the flame.
+    flame: bool,
+    /// Seconds left in which nothing can hurt the ship, given on respawn.
+    invulnerable: f64,
+}
+
+impl Player {
+    fn new(x: f64, y: f64) -> Player {
+        Player {
+            x,
+            y,
+            angle: 0.0,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            angular_velocity: 0.0,
+            thrusting: false,
+            flame: false,
+            invulnerable: 0.0,
+        }
+    }
+
+    fn builder(x: f64, y: f64) -> PlayerBuilder {
+        PlayerBuilder {
+            player: Player::new(x, y),
+        }
+    }
+
+    fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+
+    /// Whether an invulnerable ship is in the hidden half of its blink.
+    fn is_blinked_out(&self) -> bool {
+        self.is_invulnerable() && (self.invulnerable / INVULNERABLE_BLINK) as u32 % 2 == 1
+    }
+
+    /// Pixels per second, whichever way the ship is moving.
+    fn speed(&self) -> f64 {
+        self.velocity_x.hypot(self.velocity_y)
+    }
+
+    fn update(&mut self, bounds: Bounds, config: &GameConfig, dt: f64) {
+        self.flame = std::mem::take(&mut self.thrusting);
+        self.invulnerable = (self.invulnerable - dt).max(0.0);
+        let frames = dt / REFERENCE_FRAME;
+        let retained = (1.0 - config.ship_damping).powf(frames);
+        self.velocity_x *= retained;
+        self.velocity_y *= retained;
+        let speed = self.speed();
+        if speed > config.max_ship_speed {
+            let scale = config.max_ship_speed / speed;
+            self.velocity_x *= scale;
+            self.velocity_y *= scale;
+        }
+
+        self.x += self.velocity_x * dt;
+        self.y += self.velocity_y * dt;
+
+        if config.turning_inertia {
+            self.angle += self.angular_velocity * dt;
+            self.angular_velocity *= (1.0 - config.turn_damping).powf(frames);
+        } else {
+            self.angular_velocity = 0.0;
+        }
+
+        match config.wrap_mode {
+            WrapMode::Wrap => {
+                self.x = wrap(self.x, bounds.width, config.wrap_buffer);
+                self.y = wrap(self.y, bounds.height, config.wrap_buffer);
+            }
+            mode => {
+                (self.x, self.velocity_x) =
+                    confine(self.x, self.velocity_x, bounds.width, SHIP_RADIUS, mode);
+                (self.y, self.velocity_y) =
+                    confine(self.y, self.velocity_y, bounds.height, SHIP_RADIUS, mode);
+            }
+        }
+    }
+
+    #[cfg(feature = "web")]
+    fn draw(
+        &self,
+        context: &impl Surface,
+        color: &str,
+        flame_color: &str,
+        style: RenderStyle,
+    ) -> Result<(), JsValue> {
+        if self.is_blinked_out() {
+            return Ok(());
+        }
+        context.save();
+        context.translate(self.x, self.y)?;
+        context.rotate(self.angle)?;
+        
+        context.begin_path();
+        context.move_to(0.0, -20.0);
+        context.line_to(10.0, 10.0);
+        context.line_to(-10.0, 10.0);
+        context.close_path();
+        
+        render::paint(context, color, style);
+
+        if self.flame {
+            context.begin_path();
+            context.move_to(-5.0, 10.0);
+            context.line_to(0.0, 20.0);
+            context.line_to(5.0, 10.0);
+            context.set_stroke_style(flame_color);
+            context.stroke();
+        }
+        
+        context.restore();
+        Ok(())
+    }
+
+    fn rotate(&mut self, angle: f64) {
+        self.angle += angle;
+    }
+
+    /// Turns the ship in `direction` at the configured rate for `dt`
+    /// seconds, or with inertia on, spins it up towards that rate.
+    fn turn(&mut self, direction: f64, config: &GameConfig, dt: f64) {
+        let max = config.rotation_speed;
+        if config.turning_inertia {
+            let ramp = direction * max * config.turn_ramp * dt;
+            self.angular_velocity = (self.angular_velocity + ramp).clamp(-max, max);
+        } else {
+            self.rotate(direction * max * dt);
+        }
+    }
+
+    /// Accelerates along the engine's line for `dt` seconds.
+    fn thrust(&mut self, config: &GameConfig, dt: f64) {
+        self.thrusting = true;
+        let (sin, cos) = (self.angle + config.thrust_offset).sin_cos();
+        self.velocity_x += sin * config.thrust_power * dt;
+        self.velocity_y -= cos * config.thrust_power * dt;
+    }
+
+    /// A puff of exhaust leaving the rear of the ship.
+    fn exhaust(&self, rng: &mut impl Rng) -> Particle {
+        let (sin, cos) = self.angle.sin_cos();
+        let spread = rng.gen_range(-0.3..0.3);
+        let (spread_sin, spread_cos) = (self.angle + spread).sin_cos();
+        Particle::new(
+            self.x - sin * 10.0,
+            self.y + cos * 10.0,
+            self.velocity_x - spread_sin * EXHAUST_SPEED,
+            self.velocity_y + spread_cos * EXHAUST_SPEED,
+            THRUST_PARTICLE_LIFETIME,
+        )
+    }
+
+    /// The hull's nose and wingtips relative to its center, turned to the
+    /// current heading, matching what `draw` outlines.
+    fn hull(&self) -> [(f64, f64); 3] {
+        let (sin, cos) = self.angle.sin_cos();
+        [(0.0, -20.0), (10.0, 10.0), (-10.0, 10.0)]
+            .map(|(x, y)| (x * cos - y * sin, x * sin + y * cos))
+    }
+
+    /// Whether `asteroid` touches the triangular hull rather than the hit
+    /// circle around it, so rocks slipping past the narrow tail miss. A
+    /// rock touches when any side comes within its radius, or when its
+    /// center lies inside the hull. Distances wrap like `Bullet`'s.
+    fn collides_with(&self, asteroid: &Asteroid, bounds: Bounds) -> bool {
+        self.touches_circle((asteroid.x, asteroid.y), asteroid.size, bounds)
+    }
+
+    /// Whether a circle of `radius` at `center` touches the hull, as
+    /// `collides_with` tests asteroids.
+    fn touches_circle(&self, center: (f64, f64), radius: f64, bounds: Bounds) -> bool {
+        let (x, y) = toroidal_delta(center, (self.x, self.y), bounds);
+        let hull = self.hull();
+        let (mut left_of, mut right_of) = (false, false);
+        for i in 0..3 {
+            let (ax, ay) = hull[i];
+            let (bx, by) = hull[(i + 1) % 3];
+            let (ex, ey) = (bx - ax, by - ay);
+            let along = (((x - ax) * ex + (y - ay) * ey) / (ex * ex + ey * ey)).clamp(0.0, 1.0);
+            let (nearest_x, nearest_y) = (ax + ex * along, ay + ey * along);
+            if (x - nearest_x).hypot(y - nearest_y) < radius {
+                return true;
+            }
+            let side = ex * (y - ay) - ey * (x - ax);
+            left_of |= side < 0.0;
+            right_of |= side > 0.0;
+        }
+        !(left_of && right_of)
+    }
+
+    /// Fires a bullet `offset` radians clockwise of the nose.
+    fn shoot(&self, offset: f64) -> Bullet {
+        let (sin, cos) = (self.angle + offset).sin_cos();
+        Bullet {
+            x: self.x + sin * 20.0,
+            y: self.y - cos * 20.0,
+            velocity_x: sin * BULLET_SPEED + self.velocity_x,
+            velocity_y: -cos * BULLET_SPEED + self.velocity_y,
+            owner: BulletOwner::Player,
+            age: 0.0,
+        }
+    }
+}
+
+/// Configures a `Player` that starts already turned or moving, for scripted
+/// scenarios.
+struct PlayerBuilder {
+    player: Player,
+}
+
+impl PlayerBuilder {
+    fn heading(mut self, angle: f64) -> PlayerBuilder {
+        self.player.angle = angle;
+        self
+    }
+
+    fn velocity(mut self, velocity_x: f64, velocity_y: f64) -> PlayerBuilder {
+        self.player.velocity_x = velocity_x;
+        self.player.velocity_y = velocity_y;
+        self
+    }
+
+    fn build(self) -> Result<Player, String> {
+        let p = &self.player;
+        let values = [p.x, p.y, p.angle, p.velocity_x, p.velocity_y];
+        if values.iter().all(|v| v.is_finite()) {
+            Ok(self.player)
+        } else {
+            Err("player position, heading and velocity must be finite".to_string())
+        }
+    }
+}
+
+/// Seconds a freshly spawned asteroid stays intangible.
+const ASTEROID_SPAWN_GRACE: f64 = 1.5;
+
+/// Fastest a new asteroid drifts along each axis, in pixels per second.
+const ASTEROID_DRIFT: f64 = 60.0;
+
+/// How many vertices an asteroid's outline may have.
+const OUTLINE_POINTS: std::ops::RangeInclusive<usize> = 8..=12;
+
+/// Most an outline vertex may sit inside the bounding circle, as a fraction
+/// of the radius.
+const OUTLINE_JITTER: f64 = 0.25;
+
+/// Fastest an asteroid tumbles either way, in radians per second.
+const ASTEROID_SPIN: f64 = 1.5;
+
+/// How far back a motion trail reaches, in seconds of the asteroid's travel.
+const TRAIL_SECONDS: f64 = 0.125;
+
+/// Opacity of a motion trail.
+const TRAIL_ALPHA: f64 = 0.3;
+
+/// Asteroids smaller than this are destroyed outright instead of splitting.
+const MIN_ASTEROID_SIZE: f64 = SMALL_ASTEROID_SIZE;
+
+/// Angle in radians each fragment veers away from the parent's heading.
+const SPLIT_ANGLE: f64 = 0.5;
+
+/// How an asteroid should be outlined this frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AsteroidStyle {
+    Normal,
+    Intangible,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Asteroid {
+    /// Stable identity assigned by the world, used to order collision
+    /// resolution.
+    id: u32,
+    x: f64,
+    y: f64,
+    velocity_x: f64,
+    velocity_y: f64,
+    size: f64,
+    /// Seconds of spawn grace left.
+    grace: f64,
+    /// Set once the asteroid has scored a near miss, so it only pays once.
+    grazed: bool,
+    /// Radius of each vertex of the drawn silhouette as a fraction of
+    /// `size`, evenly spaced around it. All are at most 1, so `size` stays
+    /// the bounding circle.
+    outline: Vec<f64>,
+    /// Rotation of the drawn silhouette, in radians. Purely cosmetic:
+    /// collisions only use the bounding circle.
+    angle: f64,
+    /// Radians per second the silhouette turns by.
+    angular_velocity: f64,
+}
+
+impl Asteroid {
+    /// A medium asteroid, the size the tests mostly work with.
+    #[cfg(test)]
+    fn new(x: f64, y: f64) -> Asteroid {
+        Asteroid::with_size(x, y, wave::MEDIUM_ASTEROID_SIZE, &mut rand::thread_rng())
+    }
+
+    fn with_size(x: f64, y: f64, size: f64, rng: &mut impl Rng) -> Asteroid {
+        Asteroid {
+            id: 0,
+            x,
+            y,
+            velocity_x: rng.gen_range(-ASTEROID_DRIFT..ASTEROID_DRIFT),
+            velocity_y: rng.gen_range(-ASTEROID_DRIFT..ASTEROID_DRIFT),
+            size,
+            grace: ASTEROID_SPAWN_GRACE,
+            grazed: false,
+            outline: Asteroid::random_outline(rng),
+            angle: 0.0,
+            angular_velocity: rng.gen_range(-ASTEROID_SPIN..=ASTEROID_SPIN),
+        }
+    }
+
+    /// A jagged silhouette, fixed for the asteroid's lifetime.
+    fn random_outline(rng: &mut impl Rng) -> Vec<f64> {
+        let points = rng.gen_range(OUTLINE_POINTS);
+        (0..points).map(|_| rng.gen_range(1.0 - OUTLINE_JITTER..=1.0)).collect()
+    }
+
+    /// Points for destroying the asteroid, before any combo, by the size
+    /// class it falls in.
+    fn points(&self) -> u32 {
+        if self.size > wave::MEDIUM_ASTEROID_SIZE {
+            LARGE_ASTEROID_POINTS
+        } else if self.size > SMALL_ASTEROID_SIZE {
+            MEDIUM_ASTEROID_POINTS
+        } else {
+            SMALL_ASTEROID_POINTS
+        }
+    }
+
+    /// Mass grows with area, measured in small-asteroid units.
+    fn mass(&self) -> f64 {
+        (self.size / SMALL_ASTEROID_SIZE).powi(2)
+    }
+
+    /// Exchanges momentum with `other` if the two overlap and are moving
+    /// towards each other, using the two-body elastic collision equations.
+    fn bounce_off(&mut self, other: &mut Asteroid, bounds: Bounds) {
+        let (dx, dy) = toroidal_delta((self.x, self.y), (other.x, other.y), bounds);
+        let distance_sq = dx * dx + dy * dy;
+        let reach = self.size + other.size;
+        if distance_sq >= reach * reach || distance_sq == 0.0 {
+            return;
+        }
+        let (dvx, dvy) = (self.velocity_x - other.velocity_x, self.velocity_y - other.velocity_y);
+        let approach = dvx * dx + dvy * dy;
+        if approach >= 0.0 {
+            return;
+        }
+
+        let (m1, m2) = (self.mass(), other.mass());
+        let impulse = 2.0 * approach / ((m1 + m2) * distance_sq);
+        self.velocity_x -= impulse * m2 * dx;
+        self.velocity_y -= impulse * m2 * dy;
+        other.velocity_x += impulse * m1 * dx;
+        other.velocity_y += impulse * m1 * dy;
+    }
+
+    /// Asteroids ignore collisions until their spawn grace runs out.
+    fn is_intangible(&self) -> bool {
+        self.grace > 0.0
+    }
+
+    fn render_style(&self) -> AsteroidStyle {
+        if self.is_intangible() {
+            AsteroidStyle::Intangible
+        } else {
+            AsteroidStyle::Normal
+        }
+    }
+
+    /// Breaks the asteroid into two half-size fragments, or nothing if it is
+    /// already the smallest size. Each fragment's velocity blends, by
+    /// `split_randomness`, from its inherited velocity to a fresh one at the
+    /// same speed in a random direction. The inherited pair veers
+    /// `SPLIT_ANGLE` off either side of the parent's heading and averages
+    /// to the parent's velocity times `split_speed_multiplier`.
+    fn split(&self, config: &GameConfig, rng: &mut impl Rng) -> Vec<Asteroid> {
+        let size = self.size / 2.0;
+        if size < MIN_ASTEROID_SIZE {
+            return Vec::new();
+        }
+
+        let speed = self.velocity_x.hypot(self.velocity_y) * config.split_speed_multiplier
+            / SPLIT_ANGLE.cos();
+        let heading = self.velocity_y.atan2(self.velocity_x);
+        let blend = config.split_randomness;
+        [-SPLIT_ANGLE, SPLIT_ANGLE]
+            .into_iter()
+            .map(|offset| {
+                let (sin, cos) = (heading + offset).sin_cos();
+                let (mut velocity_x, mut velocity_y) = (cos * speed, sin * speed);
+                if blend > 0.0 {
+                    let (sin, cos) = rng.gen_range(0.0..TAU).sin_cos();
+                    velocity_x += (cos * speed - velocity_x) * blend;
+                    velocity_y += (sin * speed - velocity_y) * blend;
+                }
+                let mut fragment = Asteroid {
+                    id: 0,
+                    x: self.x,
+                    y: self.y,
+                    velocity_x,
+                    velocity_y,
+                    size,
+                    grace: 0.0,
+                    grazed: false,
+                    outline: Asteroid::random_outline(rng),
+                    angle: self.angle,
+                    angular_velocity: rng.gen_range(-ASTEROID_SPIN..=ASTEROID_SPIN),
+                };
+                fragment.clamp_speed(config.max_asteroid_speed);
+                fragment
+            })
+            .collect()
+    }
+
+    fn clamp_speed(&mut self, max_speed: f64) {
+        let speed = self.velocity_x.hypot(self.velocity_y);
+        if speed > max_speed {
+            let scale = max_speed / speed;
+            self.velocity_x *= scale;
+            self.velocity_y *= scale;
+        }
+    }
+
+    fn update(&mut self, bounds: Bounds, config: &GameConfig, dt: f64) {
+        self.clamp_speed(config.max_asteroid_speed);
+
+        self.x += self.velocity_x * dt;
+        self.y += self.velocity_y * dt;
+        self.grace = (self.grace - dt).max(0.0);
+        self.angle = (self.angle + self.angular_velocity * dt).rem_euclid(TAU);
+
+        self.x = wrap(self.x, bounds.width, config.wrap_buffer);
+        self.y = wrap(self.y, bounds.height, config.wrap_buffer);
+    }
+
+    #[cfg(feature = "web")]
+    fn draw(&self, context: &impl Surface, color: &str, style: RenderStyle) -> Result<(), JsValue> {
+        context.save();
+        if self.render_style() == AsteroidStyle::Intangible {
+            context.set_global_alpha(0.5);
+            context.set_line_dash(&[4.0, 4.0])?;
+        }
+        context.translate(self.x, self.y)?;
+        context.rotate(self.angle)?;
+
+        context.begin_path();
+        let step = TAU / self.outline.len() as f64;
+        for (i, scale) in self.outline.iter().enumerate() {
+            let (sin, cos) = (i as f64 * step).sin_cos();
+            let (x, y) = (cos * self.size * scale, sin * self.size * scale);
+            if i == 0 {
+                context.move_to(x, y);
+            } else {
+                context.line_to(x, y);
+            }
+        }
+        context.close_path();
+        render::paint(context, color, style);
+
+        context.restore();
+        Ok(())
+    }
+
+    /// Length of the motion trail behind the asteroid: the distance it
+    /// covers in `TRAIL_SECONDS`, or nothing at or below the threshold.
+    fn trail_length(&self, config: &GameConfig) -> f64 {
+        let speed = self.velocity_x.hypot(self.velocity_y);
+        if speed > config.trail_speed_threshold {
+            speed * TRAIL_SECONDS
+        } else {
+            0.0
+        }
+    }
+
+    /// A faint line running back from the asteroid's rim, opposite its
+    /// velocity.
+    #[cfg(feature = "web")]
+    fn draw_trail(&self, context: &impl Surface, color: &str, config: &GameConfig) {
+        let length = self.trail_length(config);
+        if length == 0.0 {
+            return;
+        }
+        let speed = self.velocity_x.hypot(self.velocity_y);
+        let (back_x, back_y) = (-self.velocity_x / speed, -self.velocity_y / speed);
+        context.save();
+        context.set_global_alpha(TRAIL_ALPHA);
+        context.begin_path();
+        context.move_to(self.x + back_x * self.size, self.y + back_y * self.size);
+        let reach = self.size + length;
+        context.line_to(self.x + back_x * reach, self.y + back_y * reach);
+        context.set_stroke_style(color);
+        context.stroke();
+        context.restore();
+    }
+}
+
+/// Seconds a thrust particle lives.
+const THRUST_PARTICLE_LIFETIME: f64 = 1.0 / 3.0;
+
+/// Speed exhaust leaves the engine at, relative to the ship, in pixels per
+/// second.
+const EXHAUST_SPEED: f64 = 120.0;
+
+/// Debris particles thrown out by each destroyed asteroid.
+const EXPLOSION_PARTICLES: usize = 8;
+
+/// Seconds a debris particle lives.
+const EXPLOSION_PARTICLE_LIFETIME: f64 = 0.5;
+
+/// Fastest a debris particle leaves the blast at, in pixels per second.
+const EXPLOSION_SPEED: f64 = 90.0;
+
+/// A short-lived visual speck. Particles never collide with anything.
+#[derive(Clone)]
+struct Particle {
+    x: f64,
+    y: f64,
+    velocity_x: f64,
+    velocity_y: f64,
+    /// Seconds since the particle was emitted.
+    age: f64,
+    lifetime: f64,
+    /// Thrown out by an explosion, drawn in the asteroid color fading out
+    /// rather than along the thrust gradient.
+    debris: bool,
+}
+
+impl Particle {
+    fn new(x: f64, y: f64, velocity_x: f64, velocity_y: f64, lifetime: f64) -> Particle {
+        Particle {
+            x,
+            y,
+            velocity_x,
+            velocity_y,
+            age: 0.0,
+            lifetime,
+            debris: false,
+        }
+    }
+
+    /// A debris particle leaving `(x, y)` along `angle` at `speed`.
+    fn debris(x: f64, y: f64, angle: f64, speed: f64) -> Particle {
+        Particle {
+            debris: true,
+            ..Particle::new(
+                x,
+                y,
+                angle.cos() * speed,
+                angle.sin() * speed,
+                EXPLOSION_PARTICLE_LIFETIME,
+            )
+        }
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.x += self.velocity_x * dt;
+        self.y += self.velocity_y * dt;
+        self.age += dt;
+    }
+
+    fn is_expired(&self) -> bool {
+        self.age >= self.lifetime
+    }
+
+    /// How far through its life the particle is, from 0 at birth to 1.
+    fn age_fraction(&self) -> f64 {
+        if self.lifetime <= 0.0 {
+            return 1.0;
+        }
+        (self.age / self.lifetime).min(1.0)
+    }
+
+    fn color(&self, gradient: &[Rgba]) -> Rgba {
+        theme::gradient_at(gradient, self.age_fraction())
+    }
+
+    #[cfg(feature = "web")]
+    fn draw(&self, context: &impl Surface, gradient: &[Rgba], debris: &str) -> Result<(), JsValue> {
+        context.save();
+        context.begin_path();
+        context.arc(self.x, self.y, 1.5, 0.0, TAU)?;
+        if self.debris {
+            context.set_global_alpha(1.0 - self.age_fraction());
+            context.set_fill_style(debris);
+        } else {
+            context.set_fill_style(&self.color(gradient).css());
+        }
+        context.fill();
+        context.restore();
+        Ok(())
+    }
+}
+
+/// Points for destroying an asteroid of each size class. Smaller rocks are
+/// harder to hit and worth more, as in the arcade.
+const LARGE_ASTEROID_POINTS: u32 = 20;
+const MEDIUM_ASTEROID_POINTS: u32 = 50;
+const SMALL_ASTEROID_POINTS: u32 = 100;
+
+/// Seconds after a kill in which another one raises the combo.
+const COMBO_WINDOW: f64 = 1.5;
+
+/// Highest the combo multiplier climbs.
+const MAX_COMBO: u32 = 8;
+
+/// Seconds a score popup stays on screen.
+const POPUP_LIFETIME: f64 = 0.75;
+
+/// Pixels per second a score popup rises.
+const POPUP_RISE: f64 = 45.0;
+
+/// The points for a kill, floating up from where it happened and fading out.
+#[derive(Clone)]
+struct ScorePopup {
+    x: f64,
+    y: f64,
+    points: u32,
+    age: f64,
+}
+
+impl ScorePopup {
+    fn new(x: f64, y: f64, points: u32) -> ScorePopup {
+        ScorePopup { x, y, points, age: 0.0 }
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.y -= POPUP_RISE * dt;
+        self.age += dt;
+    }
+
+    fn is_expired(&self) -> bool {
+        self.age >= POPUP_LIFETIME
+    }
+
+    /// Fully opaque when spawned, fading linearly to nothing.
+    fn alpha(&self) -> f64 {
+        1.0 - (self.age / POPUP_LIFETIME).min(1.0)
+    }
+
+    #[cfg(feature = "web")]
+    fn draw(&self, context: &impl Surface, color: &str) -> Result<(), JsValue> {
+        context.save();
+        context.set_global_alpha(self.alpha());
+        context.set_fill_style(color);
+        context.set_font("14px monospace");
+        context.fill_text(&self.points.to_string(), self.x, self.y)?;
+        context.restore();
+        Ok(())
+    }
+}
+
+/// The built-in pause screen: the frozen frame dimmed behind a caption.
+#[derive(Clone, Debug, PartialEq)]
+struct PauseOverlay {
+    /// Opacity of the black wash laid over the frame, from 0 to 1.
+    dim: f64,
+    text: String,
+}
+
+impl PauseOverlay {
+    #[cfg(feature = "web")]
+    fn draw(
+        &self,
+        context: &impl Surface,
+        (left, top, width, height): (f64, f64, f64, f64),
+        color: &str,
+    ) -> Result<(), JsValue> {
+        context.save();
+        context.set_global_alpha(self.dim);
+        context.set_fill_style("black");
+        context.begin_path();
+        context.rect(left, top, width, height);
+        context.fill();
+        context.restore();
+
+        if !self.text.is_empty() {
+            context.set_fill_style(color);
+            context.set_font("32px monospace");
+            // Roughly centered; monospace glyphs are about 0.6em wide.
+            let text_width = self.text.chars().count() as f64 * 19.2;
+            let x = left + (width - text_width) / 2.0;
+            context.fill_text(&self.text, x, top + height / 2.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Seconds a shockwave ring takes to expand and fade.
+const SHOCKWAVE_LIFETIME: f64 = 1.0 / 3.0;
+
+/// How far a shockwave spreads, as a multiple of the asteroid's radius.
+const SHOCKWAVE_SCALE: f64 = 2.5;
+
+/// A ring that bursts out from a destroyed asteroid and fades as it grows.
+#[derive(Clone)]
+struct Shockwave {
+    x: f64,
+    y: f64,
+    max_radius: f64,
+    age: f64,
+}
+
+impl Shockwave {
+    /// A ring for an explosion of an asteroid with radius `size`.
+    fn new(x: f64, y: f64, size: f64) -> Shockwave {
+        Shockwave {
+            x,
+            y,
+            max_radius: size * SHOCKWAVE_SCALE,
+            age: 0.0,
+        }
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.age += dt;
+    }
+
+    fn is_expired(&self) -> bool {
+        self.age >= SHOCKWAVE_LIFETIME
+    }
+
+    fn progress(&self) -> f64 {
+        (self.age / SHOCKWAVE_LIFETIME).min(1.0)
+    }
+
+    fn radius(&self) -> f64 {
+        self.max_radius * self.progress()
+    }
+
+    #[cfg(feature = "web")]
+    fn draw(&self, context: &impl Surface, color: &str) -> Result<(), JsValue> {
+        context.save();
+        context.set_global_alpha(1.0 - self.progress());
+        context.begin_path();
+        context.arc(self.x, self.y, self.radius(), 0.0, TAU)?;
+        context.set_stroke_style(color);
+        context.stroke();
+        context.restore();
+        Ok(())
+    }
+}
+
+/// Seconds between flying saucers, counted while none is on screen.
+const UFO_INTERVAL: f64 = 20.0;
+
+/// Speed a saucer crosses the field at, in pixels per second.
+const UFO_SPEED: f64 = 90.0;
+
+/// Half-width of a saucer, for drawing and being hit.
+const UFO_RADIUS: f64 = 15.0;
+
+/// Seconds between a saucer's shots.
+const UFO_FIRE_INTERVAL: f64 = 1.5;
+
+/// Speed of a saucer's shots, in pixels per second.
+const UFO_BULLET_SPEED: f64 = 300.0;
+
+/// Points awarded for shooting down a saucer.
+const UFO_POINTS: u32 = 200;
+
+/// A flying saucer that drifts across the field from one side to the other,
+/// firing at the ship as it goes.
+#[derive(Clone, Serialize, Deserialize)]
+struct Ufo {
+    x: f64,
+    y: f64,
+    velocity_x: f64,
+    velocity_y: f64,
+    /// Seconds until the next shot.
+    fire_timer: f64,
+}
+
+impl Ufo {
+    /// A saucer entering just off the left or right edge, heading across.
+    fn spawn(bounds: Bounds, rng: &mut impl Rng) -> Ufo {
+        let from_left = rng.gen_bool(0.5);
+        Ufo {
+            x: if from_left { -UFO_RADIUS } else { bounds.width + UFO_RADIUS },
+            y: rng.gen_range(0.0..bounds.height),
+            velocity_x: if from_left { UFO_SPEED } else { -UFO_SPEED },
+            velocity_y: rng.gen_range(-UFO_SPEED / 3.0..=UFO_SPEED / 3.0),
+            fire_timer: UFO_FIRE_INTERVAL,
+        }
+    }
+
+    /// Moves the saucer and runs down its fire timer. It wraps top to
+    /// bottom but not side to side, so it leaves once it has crossed.
+    fn update(&mut self, bounds: Bounds, dt: f64) {
+        self.x += self.velocity_x * dt;
+        self.y = wrap(self.y + self.velocity_y * dt, bounds.height, UFO_RADIUS);
+        self.fire_timer -= dt;
+    }
+
+    /// Whether the saucer is off the edge it was flying towards.
+    fn has_crossed(&self, bounds: Bounds) -> bool {
+        if self.velocity_x > 0.0 {
+            self.x > bounds.width + UFO_RADIUS
+        } else {
+            self.x < -UFO_RADIUS
+        }
+    }
+
+    /// A shot aimed straight at `target`, taking the short way across the
+    /// wrap seams.
+    fn fire_at(&self, target: (f64, f64), bounds: Bounds) -> Bullet {
+        let (dx, dy) = toroidal_delta(target, (self.x, self.y), bounds);
+        let distance = dx.hypot(dy).max(f64::EPSILON);
+        Bullet {
+            x: self.x,
+            y: self.y,
+            velocity_x: dx / distance * UFO_BULLET_SPEED,
+            velocity_y: dy / distance * UFO_BULLET_SPEED,
+            owner: BulletOwner::Enemy,
+            age: 0.0,
+        }
+    }
+
+    /// The classic saucer: a flat hull with a dome on top.
+    #[cfg(feature = "web")]
+    fn draw(&self, context: &impl Surface, color: &str) -> Result<(), JsValue> {
+        let (r, h) = (UFO_RADIUS, UFO_RADIUS * 0.4);
+        context.begin_path();
+        context.move_to(self.x - r, self.y);
+        context.line_to(self.x - h, self.y - h);
+        context.line_to(self.x + h, self.y - h);
+        context.line_to(self.x + r, self.y);
+        context.line_to(self.x + h, self.y + h);
+        context.line_to(self.x - h, self.y + h);
+        context.close_path();
+        context.move_to(self.x - r, self.y);
+        context.line_to(self.x + r, self.y);
+        context.move_to(self.x - h, self.y - h);
+        context.arc(self.x, self.y - h, h, PI, TAU)?;
+        context.set_stroke_style(color);
+        context.stroke();
+        Ok(())
+    }
+}
+
+/// Radius bullets are drawn and collide with.
+const BULLET_RADIUS: f64 = 2.0;
+
+/// Muzzle speed of a player bullet, in pixels per second, on top of the
+/// ship's own velocity.
+const BULLET_SPEED: f64 = 600.0;
+
+/// Seconds a bullet flies before it fizzles out.
+const BULLET_LIFETIME: f64 = 1.0;
+
+/// Seconds the ship must wait between shots.
+const FIRE_COOLDOWN: f64 = 0.25;
+
+/// Seconds the ship must wait between hyperspace jumps.
+const HYPERSPACE_COOLDOWN: f64 = 1.0;
+
+/// Who fired a bullet, which decides what it can hit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum BulletOwner {
+    Player,
+    Enemy,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Bullet {
+    x: f64,
+    y: f64,
+    velocity_x: f64,
+    velocity_y: f64,
+    owner: BulletOwner,
+    /// Seconds since the bullet was fired.
+    age: f64,
+}
+
+impl Bullet {
+    /// Moves the bullet, wrapping it across the edges like everything else,
+    /// and ages it.
+    fn update(&mut self, bounds: Bounds, config: &GameConfig, dt: f64) {
+        let (gravity_x, gravity_y) = config.bullet_gravity;
+        self.velocity_x += gravity_x * dt;
+        self.velocity_y += gravity_y * dt;
+        self.x = wrap(self.x + self.velocity_x * dt, bounds.width, 0.0);
+        self.y = wrap(self.y + self.velocity_y * dt, bounds.height, 0.0);
+        self.age += dt;
+    }
+
+    fn is_expired(&self) -> bool {
+        self.age >= BULLET_LIFETIME
+    }
+
+    /// Bullets ignore everything they touch until they have flown for the
+    /// arming time.
+    fn is_armed(&self, config: &GameConfig) -> bool {
+        self.age >= config.bullet_arming_time
+    }
+
+    #[cfg(feature = "web")]
+    fn draw(&self, context: &impl Surface, color: &str) -> Result<(), JsValue> {
+        context.begin_path();
+        context.arc(self.x, self.y, BULLET_RADIUS, 0.0, TAU)?;
+        context.set_fill_style(color);
+        context.fill();
+        Ok(())
+    }
+
+    /// Tests against the asteroid's bounding circle rather than its jagged
+    /// outline. Distances are measured across the wrap seams, so a bullet
+    /// and a rock on opposite edges can still touch.
+    fn collides_with(&self, asteroid: &Asteroid, bounds: Bounds) -> bool {
+        toroidal_distance((self.x, self.y), (asteroid.x, asteroid.y), bounds) < asteroid.size
+    }
+
+    fn collides_with_bullet(&self, other: &Bullet, bounds: Bounds) -> bool {
+        toroidal_distance((self.x, self.y), (other.x, other.y), bounds) < BULLET_RADIUS * 2.0
+    }
+
+    /// Bounces off the surface of `asteroid`, mirroring the velocity about
+    /// the surface normal. A bullet already heading outwards is left alone.
+    fn deflect_off(&mut self, asteroid: &Asteroid, bounds: Bounds) {
+        let (dx, dy) = toroidal_delta((self.x, self.y), (asteroid.x, asteroid.y), bounds);
+        let distance = dx.hypot(dy);
+        if distance == 0.0 {
+            return;
+        }
+        let (nx, ny) = (dx / distance, dy / distance);
+        let along = self.velocity_x * nx + self.velocity_y * ny;
+        if along < 0.0 {
+            self.velocity_x -= 2.0 * along * nx;
+            self.velocity_y -= 2.0 * along * ny;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::MAX_SHIP_SPEED;
+    #[cfg(feature = "web")]
+    use render::Recorder;
+    use wave::{LARGE_ASTEROID_SIZE, MEDIUM_ASTEROID_SIZE};
+
+    /// One 60 Hz frame, the step most tests advance by.
+    const FRAME: f64 = REFERENCE_FRAME;
+
+    /// A fresh player bullet at `(x, y)`.
+    fn player_bullet(x: f64, y: f64, velocity_x: f64, velocity_y: f64) -> Bullet {
+        Bullet {
+            x,
+            y,
+            velocity_x,
+            velocity_y,
+            owner: BulletOwner::Player,
+            age: 0.0,
+        }
+    }
+
+    /// A motionless asteroid with no spawn grace, so it can be hit at once.
+    fn still_rock(x: f64, y: f64, size: f64, rng: &mut impl Rng) -> Asteroid {
+        let mut rock = Asteroid::with_size(x, y, size, rng);
+        (rock.velocity_x, rock.velocity_y, rock.grace) = (0.0, 0.0, 0.0);
+        rock
+    }
+
+    #[test]
+    fn test_player_creation() {
+        let player = Player::new(100.0, 100.0);
+        assert_eq!(player.x, 100.0);
+        assert_eq!(player.y, 100.0);
+        assert_eq!(player.angle, 0.0);
+        assert_eq!(player.velocity_x, 0.0);
+        assert_eq!(player.velocity_y, 0.0);
+    }
+
+    #[test]
+    fn test_player_speed() {
+        let player = Player::builder(0.0, 0.0).velocity(-30.0, 40.0).build().unwrap();
+        assert_eq!(player.speed(), 50.0);
+        assert_eq!(Player::new(0.0, 0.0).speed(), 0.0);
+    }
+
+    #[test]
+    fn test_player_builder() {
+        let player = Player::builder(50.0, 60.0)
+            .heading(1.25)
+            .velocity(2.0, -3.0)
+            .build()
+            .unwrap();
+        assert_eq!((player.x, player.y), (50.0, 60.0));
+        assert_eq!(player.angle, 1.25);
+        assert_eq!(player.velocity_x, 2.0);
+        assert_eq!(player.velocity_y, -3.0);
+
+        assert!(Player::builder(0.0, 0.0).velocity(f64::NAN, 0.0).build().is_err());
+        assert!(Player::builder(0.0, 0.0).heading(f64::INFINITY).build().is_err());
+    }
+
+    #[test]
+    fn test_player_rotation() {
+        let mut player = Player::new(100.0, 100.0);
+        player.rotate(0.5);
+        assert!((player.angle - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_thrust_offset() {
+        let config = GameConfig {
+            thrust_offset: 0.3,
+            ..GameConfig::default()
+        };
+        let mut player = Player::builder(100.0, 100.0).heading(1.0).build().unwrap();
+        player.thrust(&config, 1.0);
+        // Heading 0 points up the screen, so a heading of `a` pushes along
+        // (sin a, -cos a).
+        let direction = player.velocity_x.atan2(-player.velocity_y);
+        assert!((direction - 1.3).abs() < 1e-9);
+        assert!((player.velocity_x.hypot(player.velocity_y) - config.thrust_power).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_turning_inertia() {
+        let bounds = Bounds::new(500.0, 500.0);
+        let mut config = GameConfig::default();
+        let mut player = Player::new(100.0, 100.0);
+        player.turn(1.0, &config, FRAME);
+        player.update(bounds, &config, FRAME);
+        let released = player.angle;
+        player.update(bounds, &config, FRAME);
+        assert_eq!(player.angle, released);
+
+        config.turning_inertia = true;
+        let mut player = Player::new(100.0, 100.0);
+        for _ in 0..10 {
+            player.turn(1.0, &config, FRAME);
+            player.update(bounds, &config, FRAME);
+        }
+        assert!(player.angle < 10.0 * config.rotation_speed * FRAME);
+        let released = player.angle;
+        player.update(bounds, &config, FRAME);
+        assert!(player.angle > released);
+        for _ in 0..200 {
+            player.update(bounds, &config, FRAME);
+        }
+        assert!(player.angular_velocity.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_player_thrust() {
+        let mut player = Player::new(100.0, 100.0);
+        player.thrust(&GameConfig::default(), FRAME);
+        assert!(player.velocity_x != 0.0 || player.velocity_y != 0.0);
+    }
+
+    #[test]
+    fn test_player_speed_is_capped() {
+        let bounds = Bounds::new(500.0, 500.0);
+        let config = GameConfig::default();
+        let mut player = Player::new(100.0, 100.0);
+        for _ in 0..600 {
+            player.thrust(&config, FRAME);
+            player.update(bounds, &config, FRAME);
+            assert!(player.velocity_x.hypot(player.velocity_y) <= MAX_SHIP_SPEED + 1e-9);
+        }
+        assert!(player.velocity_x.hypot(player.velocity_y) > MAX_SHIP_SPEED * 0.9);
+    }
+
+    #[test]
+    fn test_player_drag() {
+        let bounds = Bounds::new(500.0, 500.0);
+        let config = GameConfig::default();
+        let mut player = Player::new(100.0, 100.0);
+        player.velocity_x = 300.0;
+        player.velocity_y = -300.0;
+        let mut speed = player.velocity_x.hypot(player.velocity_y);
+        for _ in 0..900 {
+            player.update(bounds, &config, FRAME);
+            let slower = player.velocity_x.hypot(player.velocity_y);
+            assert!(slower < speed);
+            speed = slower;
+        }
+        assert!(speed < 1.0);
+    }
+
+    #[test]
+    fn test_player_movement() {
+        let bounds = Bounds::new(500.0, 500.0);
+        let config = GameConfig {
+            ship_damping: 0.0,
+            ..GameConfig::default()
+        };
+        let mut player = Player::new(100.0, 100.0);
+        player.velocity_x = 120.0;
+        player.velocity_y = -60.0;
+        player.update(bounds, &config, 0.25);
+        assert_eq!((player.x, player.y), (130.0, 85.0));
+
+        // The same time split into shorter frames covers the same ground.
+        let mut stepped = Player::new(100.0, 100.0);
+        stepped.velocity_x = 120.0;
+        stepped.velocity_y = -60.0;
+        for _ in 0..4 {
+            stepped.update(bounds, &config, 0.0625);
+        }
+        assert_eq!((stepped.x, stepped.y), (player.x, player.y));
+    }
+
+    #[test]
+    fn test_wrap_far_out_of_bounds() {
+        let config = GameConfig::default();
+        let mut player = Player::new(500.0 * 5.0 + 30.0, -500.0 * 3.0);
+        player.update(Bounds::new(500.0, 500.0), &config, FRAME);
+        assert!((-20.0..520.0).contains(&player.x));
+        assert!((-20.0..520.0).contains(&player.y));
+
+        let mut asteroid = Asteroid::new(-2000.0, 4000.0);
+        asteroid.velocity_x = 0.0;
+        asteroid.velocity_y = 0.0;
+        asteroid.update(Bounds::new(500.0, 500.0), &config, FRAME);
+        assert!((-20.0..520.0).contains(&asteroid.x));
+        assert!((-20.0..520.0).contains(&asteroid.y));
+    }
+
+    #[test]
+    fn test_wrap_buffer() {
+        let config = GameConfig {
+            wrap_buffer: 10.0,
+            ..GameConfig::default()
+        };
+        let bounds = Bounds::new(500.0, 500.0);
+        let mut player = Player::new(505.0, 250.0);
+        player.update(bounds, &config, FRAME);
+        assert_eq!(player.x, 505.0);
+
+        player.x = 512.0;
+        player.update(bounds, &config, FRAME);
+        assert_eq!(player.x, 12.0);
+
+        player.x = -11.0;
+        player.update(bounds, &config, FRAME);
+        assert_eq!(player.x, 489.0);
+
+        assert_eq!(wrap_offsets((505.0, 250.0, 20.0), bounds), vec![(0.0, 0.0), (-500.0, 0.0)]);
+        assert_eq!(wrap_offsets((250.0, 250.0, 20.0), bounds), vec![(0.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_wrap_mode_wrap() {
+        let config = GameConfig {
+            wrap_buffer: 0.0,
+            ..GameConfig::default()
+        };
+        assert_eq!(config.wrap_mode, WrapMode::Wrap);
+        let mut player = Player::new(495.0, 250.0);
+        player.velocity_x = 600.0;
+        player.update(Bounds::new(500.0, 500.0), &config, FRAME);
+        assert!(player.x < 10.0);
+        assert!(player.velocity_x > 0.0);
+    }
+
+    #[test]
+    fn test_wrap_mode_clamp() {
+        let config = GameConfig {
+            wrap_mode: WrapMode::from_name("Clamp").unwrap(),
+            ..GameConfig::default()
+        };
+        let bounds = Bounds::new(500.0, 500.0);
+        let mut player = Player::new(495.0, 10.0);
+        (player.velocity_x, player.velocity_y) = (600.0, -300.0);
+        player.update(bounds, &config, FRAME);
+        assert_eq!((player.x, player.y), (500.0 - SHIP_RADIUS, SHIP_RADIUS));
+        assert_eq!((player.velocity_x, player.velocity_y), (0.0, 0.0));
+
+        // Heading back into the field keeps its speed
+        player.x = 495.0;
+        player.velocity_x = -60.0;
+        player.update(bounds, &config, FRAME);
+        assert!(player.velocity_x < 0.0);
+        assert_eq!(WrapMode::from_name("sticky"), None);
+    }
+
+    #[test]
+    fn test_wrap_mode_bounce() {
+        let config = GameConfig {
+            wrap_mode: WrapMode::Bounce,
+            ship_damping: 0.0,
+            ..GameConfig::default()
+        };
+        let bounds = Bounds::new(500.0, 500.0);
+        let mut player = Player::new(SHIP_RADIUS + 1.0, 250.0);
+        player.velocity_x = -120.0;
+        player.update(bounds, &config, FRAME);
+        assert_eq!(player.velocity_x, 120.0);
+        assert!((player.x - (SHIP_RADIUS + 1.0)).abs() < 1e-9);
+
+        player.y = 490.0;
+        player.velocity_y = 60.0;
+        player.update(bounds, &config, FRAME);
+        assert!(player.y <= 500.0 - SHIP_RADIUS);
+        assert_eq!(player.velocity_y, -60.0);
+    }
+
+    #[test]
+    fn test_asteroid_creation() {
+        let asteroid = Asteroid::new(100.0, 100.0);
+        assert_eq!(asteroid.x, 100.0);
+        assert_eq!(asteroid.y, 100.0);
+        assert_eq!(asteroid.size, 20.0);
+        assert!(asteroid.velocity_x.abs() <= ASTEROID_DRIFT);
+        assert!(asteroid.velocity_y.abs() <= ASTEROID_DRIFT);
+    }
+
+    #[test]
+    fn test_asteroid_movement() {
+        let mut asteroid = Asteroid::new(100.0, 100.0);
+        let initial_x = asteroid.x;
+        let initial_y = asteroid.y;
+        asteroid.update(Bounds::new(500.0, 500.0), &GameConfig::default(), FRAME);
+        assert!(asteroid.x != initial_x || asteroid.y != initial_y);
+    }
+
+    #[test]
+    fn test_asteroid_spawn_grace_style() {
+        let mut asteroid = Asteroid::new(100.0, 100.0);
+        assert!(asteroid.is_intangible());
+        assert_eq!(asteroid.render_style(), AsteroidStyle::Intangible);
+
+        let bounds = Bounds::new(500.0, 500.0);
+        asteroid.update(bounds, &GameConfig::default(), ASTEROID_SPAWN_GRACE / 2.0);
+        assert!(asteroid.is_intangible());
+        asteroid.update(bounds, &GameConfig::default(), ASTEROID_SPAWN_GRACE / 2.0);
+        assert!(!asteroid.is_intangible());
+        assert_eq!(asteroid.render_style(), AsteroidStyle::Normal);
+    }
+
+    #[test]
+    fn test_asteroid_speed_clamp() {
+        let config = GameConfig {
+            max_asteroid_speed: 180.0,
+            ..GameConfig::default()
+        };
+        let mut asteroid = Asteroid::new(100.0, 100.0);
+        asteroid.velocity_x = 1800.0;
+        asteroid.velocity_y = 2400.0;
+        asteroid.update(Bounds::new(500.0, 500.0), &config, FRAME);
+        let speed = asteroid.velocity_x.hypot(asteroid.velocity_y);
+        assert!((speed - 180.0).abs() < 1e-9);
+        assert!((asteroid.velocity_x / asteroid.velocity_y - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_asteroid_split_speed() {
+        let config = GameConfig {
+            split_speed_multiplier: 1.5,
+            ..GameConfig::default()
+        };
+        let mut rng = Pcg32::seed_from_u64(3);
+        let mut parent = Asteroid::new(100.0, 100.0);
+        parent.velocity_x = 60.0;
+        parent.velocity_y = 30.0;
+        let parent_speed = parent.velocity_x.hypot(parent.velocity_y);
+
+        let fragments = parent.split(&config, &mut rng);
+        assert_eq!(fragments.len(), 2);
+        let average = fragments
+            .iter()
+            .map(|f| f.velocity_x.hypot(f.velocity_y))
+            .sum::<f64>()
+            / 2.0;
+        assert!(average > parent_speed);
+        assert!(average <= config.max_asteroid_speed);
+
+        parent.velocity_x = 600.0;
+        for fragment in parent.split(&config, &mut rng) {
+            assert!(fragment.velocity_x.hypot(fragment.velocity_y) <= config.max_asteroid_speed + 1e-9);
+            assert!(fragment.split(&config, &mut rng).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_split_randomness() {
+        let mut config = GameConfig {
+            split_speed_multiplier: 1.0,
+            ..GameConfig::default()
+        };
+        let mut rng = Pcg32::seed_from_u64(5);
+        let mut parent = Asteroid::with_size(100.0, 100.0, LARGE_ASTEROID_SIZE, &mut rng);
+        (parent.velocity_x, parent.velocity_y) = (40.0, -30.0);
+        let heading = parent.velocity_y.atan2(parent.velocity_x);
+        let headings = |config: &GameConfig, rng: &mut Pcg32| -> Vec<f64> {
+            (0..20)
+                .flat_map(|_| parent.split(config, rng))
+                .map(|f| f.velocity_y.atan2(f.velocity_x))
+                .collect()
+        };
+
+        let fragments = parent.split(&config, &mut rng);
+        let average = (
+            (fragments[0].velocity_x + fragments[1].velocity_x) / 2.0,
+            (fragments[0].velocity_y + fragments[1].velocity_y) / 2.0,
+        );
+        assert!((average.0 - 40.0).abs() < 1e-9 && (average.1 + 30.0).abs() < 1e-9);
+        let turned = |h: f64| (h - heading + PI).rem_euclid(TAU) - PI;
+        assert!(headings(&config, &mut rng).iter().all(|&h| turned(h).abs() <= SPLIT_ANGLE + 1e-9));
+
+        config.split_randomness = 1.0;
+        let random = headings(&config, &mut rng);
+        assert!(random.iter().any(|&h| turned(h).abs() > PI / 2.0));
+        assert!(random.iter().any(|&h| turned(h).abs() < PI / 2.0));
+    }
+
+    #[test]
+    fn test_asteroid_trail_length() {
+        let config = GameConfig::default();
+        let mut asteroid = Asteroid::new(100.0, 100.0);
+        asteroid.velocity_x = 96.0;
+        asteroid.velocity_y = 72.0;
+        assert_eq!(asteroid.trail_length(&config), 0.0);
+
+        asteroid.velocity_x = 160.0;
+        asteroid.velocity_y = 120.0;
+        let slow = asteroid.trail_length(&config);
+        assert_eq!(slow, 200.0 * TRAIL_SECONDS);
+        asteroid.velocity_x *= 2.0;
+        asteroid.velocity_y *= 2.0;
+        assert_eq!(asteroid.trail_length(&config), 2.0 * slow);
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_thrust_flame() {
+        let bounds = Bounds::new(500.0, 500.0);
+        let config = GameConfig::default();
+        let mut player = Player::new(100.0, 100.0);
+        let draw = |player: &Player| {
+            let surface = Recorder::default();
+            player.draw(&surface, "white", "orange", RenderStyle::Wireframe).unwrap();
+            surface.contains("stroke_style(orange)")
+        };
+
+        player.thrust(&config, FRAME);
+        player.update(bounds, &config, FRAME);
+        assert!(draw(&player));
+        player.update(bounds, &config, FRAME);
+        assert!(!draw(&player));
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_invulnerable_ship_blinks() {
+        let mut player = Player::new(100.0, 100.0);
+        let drawn = |player: &Player| {
+            let surface = Recorder::default();
+            player.draw(&surface, "white", "orange", RenderStyle::Wireframe).unwrap();
+            !surface.calls.into_inner().is_empty()
+        };
+        assert!(drawn(&player));
+        player.invulnerable = 1.5 * INVULNERABLE_BLINK;
+        assert!(!drawn(&player));
+        player.invulnerable = 0.5 * INVULNERABLE_BLINK;
+        assert!(drawn(&player));
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_asteroid_outline() {
+        let mut rng = Pcg32::seed_from_u64(8);
+        let asteroid = Asteroid::with_size(100.0, 100.0, LARGE_ASTEROID_SIZE, &mut rng);
+        assert!(OUTLINE_POINTS.contains(&asteroid.outline.len()));
+        assert!(asteroid.outline.iter().all(|&r| (1.0 - OUTLINE_JITTER..=1.0).contains(&r)));
+
+        let draw = || {
+            let surface = Recorder::default();
+            asteroid.draw(&surface, "white", RenderStyle::Wireframe).unwrap();
+            surface.calls.into_inner()
+        };
+        let calls = draw();
+        let lines = calls.iter().filter(|c| c.starts_with("line_to")).count();
+        assert_eq!(lines, asteroid.outline.len() - 1);
+        assert!(calls.contains(&"close_path".to_string()));
+        assert!(!calls.iter().any(|c| c.starts_with("arc")));
+        assert_eq!(draw(), calls);
+    }
+
+    #[test]
+    fn test_asteroid_rotation() {
+        let bounds = Bounds::new(800.0, 600.0);
+        let config = GameConfig::default();
+        let mut asteroid = Asteroid::new(400.0, 300.0);
+        asteroid.angular_velocity = 1.0;
+        for _ in 0..4 {
+            asteroid.update(bounds, &config, 0.25);
+        }
+        assert!((asteroid.angle - 1.0).abs() < 1e-9);
+
+        let mut rng = Pcg32::seed_from_u64(3);
+        let spins: Vec<f64> = (0..8)
+            .map(|_| Asteroid::with_size(0.0, 0.0, 20.0, &mut rng).angular_velocity)
+            .collect();
+        assert!(spins.iter().all(|w| w.abs() <= ASTEROID_SPIN));
+        assert!(spins.iter().any(|&w| w != spins[0]));
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_asteroid_filled_style() {
+        let asteroid = Asteroid::new(100.0, 100.0);
+
+        let surface = Recorder::default();
+        asteroid.draw(&surface, "white", RenderStyle::Wireframe).unwrap();
+        assert!(surface.contains("stroke"));
+        assert!(!surface.contains("fill"));
+
+        let surface = Recorder::default();
+        asteroid.draw(&surface, "white", RenderStyle::Filled).unwrap();
+        assert!(surface.contains("fill_style(white)"));
+        assert!(surface.contains("fill"));
+    }
+
+    #[test]
+    fn test_asteroid_mass_collision() {
+        let bounds = Bounds::new(800.0, 600.0);
+        let mut rng = Pcg32::seed_from_u64(6);
+        let mut large = Asteroid::with_size(100.0, 100.0, LARGE_ASTEROID_SIZE, &mut rng);
+        let mut small = Asteroid::with_size(145.0, 100.0, SMALL_ASTEROID_SIZE, &mut rng);
+        (large.velocity_x, large.velocity_y) = (1.0, 0.0);
+        (small.velocity_x, small.velocity_y) = (-1.0, 0.0);
+        let momentum = large.mass() * large.velocity_x + small.mass() * small.velocity_x;
+
+        large.bounce_off(&mut small, bounds);
+        let large_change = (large.velocity_x - 1.0).abs();
+        let small_change = (small.velocity_x + 1.0).abs();
+        assert!(small_change > large_change);
+        assert!(small.velocity_x > 0.0);
+        let after = large.mass() * large.velocity_x + small.mass() * small.velocity_x;
+        assert!((after - momentum).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_disabled_entity_types() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.particles_enabled = false;
+        world.config.power_ups_enabled = false;
+        world.thrust();
+        assert!(world.particles.is_empty());
+        world.activate_magnet();
+        assert_eq!(world.magnet_time, 0.0);
+
+        world.config.particles_enabled = true;
+        world.thrust();
+        assert_eq!(world.particles.len(), 1);
+        world.config.particles_enabled = false;
+        world.update(FRAME);
+        assert!(world.particles.is_empty());
+    }
+
+    #[test]
+    fn test_thrust_particle_gradient() {
+        let gradient = Theme::default().thrust_gradient;
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.thrust();
+        assert_eq!(world.particles.len(), 1);
+        assert_eq!(world.particles[0].color(&gradient), gradient[0]);
+
+        let mut particle = world.particles[0].clone();
+        particle.update(THRUST_PARTICLE_LIFETIME / 2.0);
+        assert!(!particle.is_expired());
+        assert_ne!(particle.color(&gradient), gradient[0]);
+        particle.update(THRUST_PARTICLE_LIFETIME / 2.0);
+        assert!(particle.is_expired());
+        assert_eq!(particle.color(&gradient), *gradient.last().unwrap());
+
+        assert!(world.run_until(FRAME, 30, |w| w.particles.is_empty()));
+    }
+
+    #[test]
+    fn test_bullet_creation() {
+        let player = Player::new(100.0, 100.0);
+        let bullet = player.shoot(0.0);
+        assert!(bullet.x > 0.0);
+        assert!(bullet.y > 0.0);
+        assert!(bullet.velocity_x != 0.0 || bullet.velocity_y != 0.0);
+    }
+
+    #[test]
+    fn test_bullet_movement() {
+        let mut bullet = player_bullet(100.0, 100.0, 1.0, 1.0);
+        let initial_x = bullet.x;
+        let initial_y = bullet.y;
+        bullet.update(Bounds::new(500.0, 500.0), &GameConfig::default(), 0.5);
+        assert_eq!(bullet.x, initial_x + 0.5);
+        assert_eq!(bullet.y, initial_y + 0.5);
+    }
+
+    #[test]
+    fn test_bullet_wraps_and_expires() {
+        let bounds = Bounds::new(500.0, 500.0);
+        let mut bullet = player_bullet(495.0, 100.0, 640.0, 0.0);
+        bullet.update(bounds, &GameConfig::default(), 1.0 / 64.0);
+        assert_eq!(bullet.x, 5.0);
+        assert!(!bullet.is_expired());
+        bullet.update(bounds, &GameConfig::default(), BULLET_LIFETIME);
+        assert!(bullet.is_expired());
+    }
+
+    #[test]
+    fn test_bullet_gravity() {
+        let config = GameConfig {
+            bullet_gravity: (0.0, 720.0),
+            ..GameConfig::default()
+        };
+        let mut bullet = player_bullet(100.0, 100.0, 600.0, 0.0);
+        let mut fall = bullet.velocity_y;
+        for _ in 0..5 {
+            bullet.update(Bounds::new(500.0, 500.0), &config, FRAME);
+            assert!(bullet.velocity_y > fall);
+            fall = bullet.velocity_y;
+        }
+        assert_eq!(bullet.velocity_x, 600.0);
+        assert!(bullet.y > 100.0);
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_bullet_color() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.asteroids.clear();
+        world.shoot();
+
+        let surface = Recorder::default();
+        world.render(&surface).unwrap();
+        assert!(surface.contains("fill_style(cyan)"));
+
+        world.theme.bullet = "#ff00ff".to_string();
+        let surface = Recorder::default();
+        world.render(&surface).unwrap();
+        assert!(surface.contains("fill_style(#ff00ff)"));
+        assert!(!surface.contains("fill_style(cyan)"));
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_ship_and_asteroid_colors() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        let surface = Recorder::default();
+        world.render(&surface).unwrap();
+        assert!(surface.contains("stroke_style(white)"));
+
+        theme::replace_color(&mut world.theme.ship, "#00ff00");
+        theme::replace_color(&mut world.theme.asteroid, "orange");
+        let surface = Recorder::default();
+        world.render(&surface).unwrap();
+        assert!(surface.contains("stroke_style(#00ff00)"));
+        assert!(surface.contains("stroke_style(orange)"));
+        assert!(!surface.contains("stroke_style(white)"));
+    }
+
+    #[test]
+    fn test_score_popup() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.bullet_arming_time = 0.0;
+        world.asteroids = vec![still_rock(100.0, 100.0, MEDIUM_ASTEROID_SIZE, &mut world.rng)];
+        world.bullets.push(player_bullet(100.0, 100.0, 0.0, 0.0));
+        world.update(FRAME);
+        assert_eq!(world.popups.len(), 1);
+        assert_eq!(world.popups[0].points, MEDIUM_ASTEROID_POINTS);
+        assert_eq!((world.popups[0].x, world.popups[0].y), (100.0, 100.0));
+
+        // Steps are capped at a quarter second, so this takes three.
+        world.update(POPUP_LIFETIME / 3.0);
+        world.update(POPUP_LIFETIME / 3.0);
+        assert_eq!(world.popups.len(), 1);
+        assert!(world.popups[0].y < 100.0);
+        world.update(POPUP_LIFETIME / 3.0);
+        assert!(world.popups.is_empty());
+    }
+
+    #[test]
+    fn test_tiered_points() {
+        let mut rng = Pcg32::seed_from_u64(4);
+        let mut points = |size| Asteroid::with_size(0.0, 0.0, size, &mut rng).points();
+        assert_eq!(points(wave::LARGE_ASTEROID_SIZE), LARGE_ASTEROID_POINTS);
+        assert_eq!(points(wave::MEDIUM_ASTEROID_SIZE), MEDIUM_ASTEROID_POINTS);
+        assert_eq!(points(SMALL_ASTEROID_SIZE), SMALL_ASTEROID_POINTS);
+    }
+
+    #[test]
+    fn test_combo_builds_and_decays() {
+        let mut world = World::seeded(Bounds::new(500.0, 500.0), 8);
+        world.config.bullet_arming_time = 0.0;
+        world.asteroids.clear();
+        let place = |world: &mut World, x, y| {
+            let rock = still_rock(x, y, SMALL_ASTEROID_SIZE, &mut world.rng);
+            world.add_asteroid(rock);
+            world.bullets.push(player_bullet(x, y, 0.0, 0.0));
+        };
+        let survivor = still_rock(450.0, 450.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        world.add_asteroid(survivor);
+
+        place(&mut world, 100.0, 100.0);
+        world.update(FRAME);
+        assert_eq!((world.combo, world.score), (1, SMALL_ASTEROID_POINTS));
+
+        world.update(COMBO_WINDOW / 2.0);
+        place(&mut world, 100.0, 400.0);
+        world.update(FRAME);
+        assert_eq!(world.combo, 2);
+        assert_eq!(world.score, 3 * SMALL_ASTEROID_POINTS);
+        assert_eq!(world.popups.last().unwrap().points, 2 * SMALL_ASTEROID_POINTS);
+
+        let frames = (COMBO_WINDOW / FRAME).ceil() as u32;
+        assert!(world.run_until(FRAME, frames, |w| w.combo == 1));
+        place(&mut world, 400.0, 100.0);
+        world.update(FRAME);
+        assert_eq!(world.score, 4 * SMALL_ASTEROID_POINTS);
+    }
+
+    #[test]
+    fn test_shockwave() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.bullet_arming_time = 0.0;
+        let rock = still_rock(100.0, 100.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        let size = rock.size;
+        world.asteroids = vec![rock];
+        world.bullets.push(player_bullet(100.0, 100.0, 0.0, 0.0));
+        world.update(FRAME);
+        assert_eq!(world.shockwaves.len(), 1);
+        assert_eq!(world.shockwaves[0].max_radius, size * SHOCKWAVE_SCALE);
+
+        let mut radius = world.shockwaves[0].radius();
+        for _ in 0..3 {
+            world.update(SHOCKWAVE_LIFETIME / 4.0);
+            let grown = world.shockwaves[0].radius();
+            assert!(grown > radius);
+            radius = grown;
+        }
+        world.update(SHOCKWAVE_LIFETIME / 2.0);
+        assert!(world.shockwaves.is_empty());
+    }
+
+    #[test]
+    fn test_explosion_particles() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.bullet_arming_time = 0.0;
+        let rock = still_rock(100.0, 100.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        world.asteroids = vec![rock];
+        world.bullets.push(player_bullet(100.0, 100.0, 0.0, 0.0));
+        world.update(FRAME);
+        assert_eq!(world.particles.iter().filter(|p| p.debris).count(), EXPLOSION_PARTICLES);
+
+        world.update(FRAME);
+        for particle in world.particles.iter().filter(|p| p.debris) {
+            let outward = (particle.x - 100.0) * particle.velocity_x
+                + (particle.y - 100.0) * particle.velocity_y;
+            assert!(outward > 0.0);
+        }
+
+        let frames = (EXPLOSION_PARTICLE_LIFETIME / FRAME).ceil() as u32;
+        assert!(world.run_until(FRAME, frames, |w| !w.particles.iter().any(|p| p.debris)));
+
+        world.config.particles_enabled = false;
+        world.explode(100.0, 100.0);
+        assert!(world.particles.is_empty());
+    }
+
+    #[test]
+    fn test_grid_hits_match_brute_force() {
+        let bounds = Bounds::new(640.0, 480.0);
+        let mut world = World::seeded(bounds, 17);
+        world.config.bullet_arming_time = 0.0;
+        world.asteroids.clear();
+        let sizes = [LARGE_ASTEROID_SIZE, MEDIUM_ASTEROID_SIZE, SMALL_ASTEROID_SIZE];
+        for n in 0..60 {
+            // Allow for the wrap buffer, so some rocks sit past the edges.
+            let x = world.rng.gen_range(-10.0..bounds.width + 10.0);
+            let y = world.rng.gen_range(-10.0..bounds.height + 10.0);
+            let mut rock = Asteroid::with_size(x, y, sizes[n % 3], &mut world.rng);
+            rock.grace = if n % 7 == 0 { 1.0 } else { 0.0 };
+            world.add_asteroid(rock);
+        }
+        for _ in 0..200 {
+            let x = world.rng.gen_range(-10.0..bounds.width + 10.0);
+            let y = world.rng.gen_range(-10.0..bounds.height + 10.0);
+            world.bullets.push(player_bullet(x, y, 0.0, 0.0));
+        }
+
+        let mut brute_force = Vec::new();
+        for (i, bullet) in world.bullets.iter().enumerate() {
+            for (j, asteroid) in world.asteroids.iter().enumerate() {
+                if !asteroid.is_intangible() && bullet.collides_with(asteroid, bounds) {
+                    brute_force.push((i, asteroid.id, j));
+                }
+            }
+        }
+        brute_force.sort_unstable_by_key(|&(bullet, id, _)| (bullet, id));
+        assert!(brute_force.len() > 10);
+        assert_eq!(world.bullet_hits(), brute_force);
+    }
+
+    #[test]
+    fn test_two_bullets_hit_two_asteroids_in_one_frame() {
+        let mut world = World::seeded(Bounds::new(500.0, 500.0), 5);
+        world.config.bullet_arming_time = 0.0;
+        world.asteroids.clear();
+        let mut ids = Vec::new();
+        for x in [100.0, 400.0] {
+            let rock = still_rock(x, 100.0, SMALL_ASTEROID_SIZE, &mut world.rng);
+            ids.push(world.next_asteroid_id);
+            world.add_asteroid(rock);
+        }
+        let survivor = still_rock(250.0, 450.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        world.add_asteroid(survivor);
+
+        // The later bullet aims at the earlier rock, and a stray follows both.
+        for x in [400.0, 100.0, 250.0] {
+            world.bullets.push(player_bullet(x, 100.0, 0.0, 0.0));
+        }
+        world.update(FRAME);
+        assert_eq!(world.wave_stats.shots_hit, 2);
+        assert!(world.asteroids.iter().all(|a| !ids.contains(&a.id)));
+        assert_eq!(world.asteroids.len(), 1);
+        assert_eq!(world.bullets.len(), 1);
+        // The second kill lands inside the combo window
+        assert_eq!(world.score, SMALL_ASTEROID_POINTS * 3);
+    }
+
+    #[test]
+    fn test_sound_cues() {
+        let mut world = World::seeded(Bounds::new(500.0, 500.0), 6);
+        world.config.bullet_arming_time = 0.0;
+        world.shoot();
+        assert!(world.sound_cues.is_empty());
+
+        world.sound = true;
+        world.shot_cooldown = 0.0;
+        world.config.pellets = 3;
+        world.shoot();
+        assert_eq!(world.sound_cues, [SoundCue::Shoot]);
+
+        world.sound_cues.clear();
+        world.bullets.clear();
+        let rock = still_rock(100.0, 100.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        world.asteroids = vec![rock];
+        world.bullets.push(player_bullet(100.0, 100.0, 0.0, 0.0));
+        world.update(FRAME);
+        assert!(world.sound_cues.contains(&SoundCue::Explosion));
+    }
+
+    #[test]
+    fn test_weak_spot_mode() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.weak_spot_mode = true;
+        world.config.bullet_arming_time = 0.0;
+        // The ship sits at (250, 250), so the weak spot faces left.
+        let rock = still_rock(350.0, 250.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        world.asteroids = vec![rock.clone()];
+        world.bullets.push(player_bullet(365.0, 250.0, -60.0, 0.0));
+        world.update(FRAME);
+        assert_eq!(world.asteroids.len(), 1);
+        assert_eq!(world.bullets.len(), 1);
+        assert!(world.bullets[0].velocity_x > 0.0);
+        assert_eq!(world.score, 0);
+
+        world.asteroids = vec![rock];
+        world.bullets = vec![player_bullet(336.0, 250.0, 60.0, 0.0)];
+        world.update(FRAME);
+        assert!(world.bullets.is_empty());
+        assert_eq!(world.score, MEDIUM_ASTEROID_POINTS);
+    }
+
+    #[test]
+    fn test_collision_detection() {
+        let bullet = player_bullet(100.0, 100.0, 0.0, 0.0);
+        let asteroid = Asteroid {
+            id: 0,
+            x: 100.0,
+            y: 100.0,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            size: 20.0,
+            grace: 0.0,
+            grazed: false,
+            outline: vec![1.0; 8],
+            angle: 0.0,
+            angular_velocity: 0.0,
+        };
+        assert!(bullet.collides_with(&asteroid, Bounds::new(500.0, 500.0)));
+
+        let bullet = player_bullet(150.0, 150.0, 0.0, 0.0);
+        assert!(!bullet.collides_with(&asteroid, Bounds::new(500.0, 500.0)));
+    }
+
+    #[test]
+    fn test_magnet() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        let mut rng = Pcg32::seed_from_u64(2);
+        let mut rock = still_rock(350.0, 250.0, SMALL_ASTEROID_SIZE, &mut rng);
+        world.asteroids = vec![rock.clone()];
+
+        world.update(FRAME);
+        assert_eq!(world.asteroids[0].velocity_x, 0.0);
+
+        world.activate_magnet();
+        world.update(FRAME);
+        assert!(world.asteroids[0].velocity_x < 0.0);
+        assert!(world.asteroids[0].x < 350.0);
+
+        rock.x = 260.0;
+        world.asteroids.push(rock);
+        let lives = world.lives;
+        world.update(FRAME);
+        assert_eq!(world.asteroids.len(), 1);
+        assert_eq!(world.score, MAGNET_BONUS);
+        assert_eq!(world.lives, lives);
+    }
+
+    #[test]
+    fn test_same_seed_same_layout() {
+        let layout = |seed| {
+            let world = World::seeded(Bounds::new(800.0, 600.0), seed);
+            world
+                .asteroids
+                .iter()
+                .map(|a| (a.x, a.y, a.velocity_x, a.velocity_y, a.size))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(layout(5), layout(5));
+        assert_ne!(layout(5), layout(6));
+    }
+
+    #[test]
+    fn test_fuzz_steps_stay_finite() {
+        let mut inputs = Pcg32::seed_from_u64(99);
+        let mut world = World::seeded(Bounds::new(800.0, 600.0), 99);
+        for step in 0..5000 {
+            let dt = match step % 7 {
+                0 => 0.0,
+                1 => f64::NAN,
+                2 => 1.0,
+                _ => inputs.gen_range(0.0..0.05),
+            };
+            world.fuzz_step(inputs.gen(), dt);
+            let player = &world.player;
+            let points = [(player.x, player.y), (player.velocity_x, player.velocity_y)]
+                .into_iter()
+                .chain(world.asteroids.iter().map(|a| (a.x, a.y)))
+                .chain(world.bullets.iter().map(|b| (b.x, b.y)))
+                .chain(world.particles.iter().map(|p| (p.x, p.y)));
+            for (x, y) in points {
+                assert!(x.is_finite() && y.is_finite(), "step {step}");
+            }
+            assert!(player.angle.is_finite());
+        }
+    }
+
+    #[test]
+    fn test_rng_state_round_trip() {
+        let mut world = World::seeded(Bounds::new(800.0, 600.0), 7);
+        world.update(FRAME);
+        let state = world.rng_state();
+        let spawn = |world: &mut World| {
+            world.asteroids.clear();
+            world.spawn_wave(WaveSpec { medium: 3, ..WaveSpec::default() });
+            world.asteroids.iter().map(|a| (a.x, a.y)).collect::<Vec<_>>()
+        };
+        let first = spawn(&mut world);
+        assert_ne!(spawn(&mut world), first);
+
+        world.set_rng_state(&state).unwrap();
+        assert_eq!(spawn(&mut world), first);
+
+        let before = world.rng_state();
+        assert!(world.set_rng_state("garbage").is_err());
+        assert_eq!(world.rng_state(), before);
+    }
+
+    #[test]
+    fn test_seeded_collisions_match() {
+        let run = || {
+            let mut world = World::seeded(Bounds::new(800.0, 600.0), 42);
+            world.config.bullet_arming_time = 0.0;
+            world.spawn_wave(WaveSpec { large: 6, medium: 6, ..WaveSpec::default() });
+            for asteroid in &mut world.asteroids {
+                asteroid.grace = 0.0;
+            }
+            // Each shot sits on a rock and its neighbour in storage order, so
+            // the shots contend for the same targets.
+            let targets: Vec<_> = world.asteroids.iter().map(|a| (a.x, a.y)).collect();
+            for pair in targets.windows(2) {
+                let (x, y) = ((pair[0].0 + pair[1].0) / 2.0, (pair[0].1 + pair[1].1) / 2.0);
+                world.bullets.push(player_bullet(x, y, 0.0, 0.0));
+                world.bullets.push(player_bullet(pair[0].0, pair[0].1, 0.0, 0.0));
+            }
+            world.update(FRAME);
+            let asteroids: Vec<_> = world
+                .asteroids
+                .iter()
+                .map(|a| (a.id, a.x, a.y, a.velocity_x, a.velocity_y, a.size))
+                .collect();
+            (asteroids, world.bullets.len(), world.score)
+        };
+        let first = run();
+        assert!(first.2 > 0);
+        assert_eq!(first, run());
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_world_tick() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.ship_damping = 0.0;
+        world.player.velocity_x = 60.0;
+        let surface = Recorder::default();
+        world.tick(&surface, 0.25);
+        assert_eq!(world.player.x, 265.0);
+        assert!(surface.contains("clear_rect(0, 0, 500, 500)"));
+        assert!(surface.contains("stroke"));
+    }
+
+    #[test]
+    fn test_world_larger_than_view() {
+        let mut world = World::new(Bounds::new(2000.0, 1500.0));
+        world.view = Some(Bounds::new(500.0, 400.0));
+        world.asteroids.clear();
+        world.config.ship_damping = 0.0;
+        world.player.x = 498.0;
+        world.player.velocity_x = 16.0;
+        world.update(0.25);
+        assert_eq!(world.player.x, 502.0);
+
+        world.player.x = 2008.0;
+        world.update(0.25);
+        assert_eq!(world.player.x, 12.0);
+
+        // The camera keeps the ship centered, and far entities are drawn at
+        // their copy nearest the ship.
+        world.player.x = 100.0;
+        world.player.y = 100.0;
+        assert_eq!(world.to_device((100.0, 100.0)), (250.0, 200.0));
+        assert_eq!(world.image_shift((1950.0, 120.0)), (-2000.0, 0.0));
+        assert_eq!(world.image_shift((400.0, 120.0)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_device_pixel_ratio() {
+        let mut world = World::new(Bounds::new(500.0, 400.0));
+        assert_eq!(world.to_device((10.0, 20.0)), (10.0, 20.0));
+        world.pixel_ratio = 2.0;
+        assert_eq!(world.to_device((10.0, 20.0)), (20.0, 40.0));
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_render_scales_to_device() {
+        let mut world = World::new(Bounds::new(500.0, 400.0));
+        world.pixel_ratio = 2.0;
+        let surface = Recorder::default();
+        world.render(&surface).unwrap();
+        assert!(surface.contains("clear_rect(0, 0, 1000, 800)"));
+        assert!(surface.contains("scale(2, 2)"));
+        assert_eq!(surface.calls.borrow().last().map(String::as_str), Some("restore"));
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_pause_overlay_only_while_paused() {
+        let mut world = World::new(Bounds::new(500.0, 400.0));
+        world.pause_overlay = Some(PauseOverlay { dim: 0.5, text: "PAUSED".to_string() });
+        let overlay_drawn = |world: &World| {
+            let surface = Recorder::default();
+            world.render(&surface).unwrap();
+            let drawn = surface.calls.borrow().iter().any(|c| c.starts_with("fill_text(PAUSED"));
+            drawn
+        };
+        assert!(!overlay_drawn(&world));
+        world.pause();
+        assert!(overlay_drawn(&world));
+        world.resume();
+        assert!(!overlay_drawn(&world));
+    }
+
+    #[test]
+    fn test_viewport_offsets_drawing() {
+        let mut world = World::new(Bounds::new(400.0, 300.0));
+        world.viewport = Some((400.0, 0.0, 400.0, 300.0));
+        assert_eq!(world.to_device((10.0, 20.0)), (410.0, 20.0));
+        world.pixel_ratio = 2.0;
+        assert_eq!(world.to_device((10.0, 20.0)), (820.0, 40.0));
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_render_into_viewport() {
+        let mut world = World::new(Bounds::new(400.0, 300.0));
+        world.viewport = Some((400.0, 0.0, 400.0, 300.0));
+        let surface = Recorder::default();
+        world.render(&surface).unwrap();
+        assert!(surface.contains("clear_rect(400, 0, 400, 300)"));
+        assert!(surface.contains("rect(400, 0, 400, 300)"));
+        assert!(surface.contains("clip"));
+        assert!(surface.contains("translate(400, 0)"));
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_tick_records_timings() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        let surface = Recorder::default();
+        world.tick(&surface, FRAME);
+        assert!(world.timings.is_none());
+
+        world.timings = Some(FrameTimings::default());
+        for _ in 0..5 {
+            world.tick(&surface, FRAME);
+        }
+        let report = world.timings.as_ref().unwrap().report();
+        assert_eq!(report.update.samples, 5);
+        assert_eq!(report.render.samples, 5);
+        assert_eq!(report.render.buckets.iter().sum::<u32>(), 5);
+        assert!(report.update.max_ms >= report.update.mean_ms);
+    }
+
+    #[test]
+    fn test_starfield() {
+        let bounds = Bounds::new(400.0, 300.0);
+        let mut world = World::seeded(bounds, 21);
+        assert_eq!(world.starfield.len(), STAR_COUNT);
+        assert_eq!(world.starfield, World::seeded(bounds, 21).starfield);
+        assert_ne!(world.starfield, World::seeded(bounds, 22).starfield);
+
+        // Drifts against the ship's motion
+        let before: Vec<_> = world.stars().collect();
+        world.player.velocity_x = 200.0;
+        world.config.ship_damping = 0.0;
+        world.update(0.25);
+        let shift = STARFIELD_PARALLAX * 200.0 * 0.25;
+        for (star, old) in world.stars().zip(&before) {
+            let moved = (old.0 - star.0).rem_euclid(bounds.width);
+            assert!((moved - shift).abs() < 1e-9);
+            assert_eq!(star.1, old.1);
+        }
+
+        // Stays spread over the field when it changes size
+        world.resize(Bounds::new(800.0, 100.0));
+        assert!(world.stars().all(|(x, y, _)| (0.0..800.0).contains(&x) && y < 100.0));
+        assert!(world.stars().any(|(x, _, _)| x > 400.0));
+    }
+
+    #[test]
+    fn test_update_is_frame_rate_independent() {
+        let bounds = Bounds::new(800.0, 600.0);
+        let mut slow = World::seeded(bounds, 11);
+        let mut fast = World::seeded(bounds, 11);
+        slow.config.ship_damping = 0.0;
+        fast.config.ship_damping = 0.0;
+        slow.player.velocity_x = 90.0;
+        fast.player.velocity_x = 90.0;
+        for _ in 0..4 {
+            slow.update(1.0 / 16.0);
+        }
+        for _ in 0..16 {
+            fast.update(1.0 / 64.0);
+        }
+        assert_eq!((slow.player.x, slow.player.y), (fast.player.x, fast.player.y));
+        for (a, b) in slow.asteroids.iter().zip(&fast.asteroids) {
+            assert!((a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9);
+        }
+        assert_eq!(slow.wave_stats.elapsed, fast.wave_stats.elapsed);
+
+        // Bad or huge steps are tamed rather than simulated.
+        let x = slow.player.x;
+        slow.update(f64::NAN);
+        slow.update(-1.0);
+        assert_eq!(slow.player.x, x);
+        slow.update(10.0);
+        assert_eq!(slow.player.x, x + 90.0 * MAX_STEP);
+    }
+
+    #[test]
+    fn test_world_uses_own_bounds() {
+        let mut world = World::new(Bounds::new(300.0, 200.0));
+        assert_eq!((world.player.x, world.player.y), (150.0, 100.0));
+        world.asteroids.clear();
+        world.config.ship_damping = 0.0;
+        world.player.x = 319.0;
+        world.player.velocity_x = 8.0;
+        world.update(0.25);
+        assert_eq!(world.player.x, 21.0);
+
+        world.bounds = Bounds::new(1000.0, 200.0);
+        world.player.x = 319.0;
+        world.update(0.25);
+        assert_eq!(world.player.x, 321.0);
+    }
+
+    #[test]
+    fn test_ship_hull_collision() {
+        let bounds = Bounds::new(500.0, 500.0);
+        let player = Player::new(250.0, 250.0);
+        let rock = |x, y| {
+            let mut asteroid = Asteroid::new(x, y);
+            asteroid.size = SMALL_ASTEROID_SIZE;
+            asteroid
+        };
+        // Grazing the right wingtip at (260, 260).
+        assert!(player.collides_with(&rock(267.0, 265.0), bounds));
+        // Inside the old hit circle, but passing under the tail.
+        assert!(!player.collides_with(&rock(250.0, 275.0), bounds));
+        assert!(player.collides_with(&rock(250.0, 255.0), bounds));
+
+        // Turned upside down, the nose points at the same rock.
+        let mut turned = player.clone();
+        turned.angle = PI;
+        assert!(turned.collides_with(&rock(250.0, 275.0), bounds));
+        // Across the seam from a ship by the edge.
+        let edge = Player::new(495.0, 250.0);
+        assert!(edge.collides_with(&rock(2.0, 255.0), bounds));
+    }
+
+    #[test]
+    fn test_ship_collision_costs_a_life() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.asteroids.clear();
+        world.player.velocity_x = 180.0;
+        world.asteroids.push(still_rock(350.0, 250.0, LARGE_ASTEROID_SIZE, &mut world.rng));
+        world.update(FRAME);
+        assert_eq!(world.lives, STARTING_LIVES);
+
+        world.player.x = 300.0;
+        world.update(FRAME);
+        assert_eq!(world.lives, STARTING_LIVES - 1);
+        assert_eq!((world.player.x, world.player.y), (250.0, 250.0));
+        assert_eq!((world.player.velocity_x, world.player.velocity_y), (0.0, 0.0));
+        assert!(!world.game_over);
+        assert!(world.player.is_invulnerable());
+
+        world.lives = 1;
+        world.player.invulnerable = 0.0;
+        world.player.x = 310.0;
+        world.update(FRAME);
+        assert_eq!(world.lives, 0);
+        assert!(world.game_over);
+    }
+
+    #[test]
+    fn test_collision_damage() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.collision_damage = true;
+        let mut rng = Pcg32::seed_from_u64(7);
+        let mut rock = |x, y, size, velocity_x| {
+            let mut asteroid = Asteroid::with_size(x, y, size, &mut rng);
+            asteroid.velocity_x = velocity_x;
+            asteroid.velocity_y = 0.0;
+            asteroid.grace = 0.0;
+            asteroid
+        };
+        // A distant rock keeps the wave from ending when the others break.
+        world.asteroids = vec![
+            rock(50.0, 50.0, MEDIUM_ASTEROID_SIZE, 0.0),
+            rock(265.0, 255.0, SMALL_ASTEROID_SIZE, 0.0),
+        ];
+        world.update(FRAME);
+        assert_eq!(world.health, 100.0 - SMALL_ASTEROID_SIZE * 1.5);
+        assert_eq!(world.lives, STARTING_LIVES);
+        assert_eq!(world.asteroids.len(), 1);
+
+        world.asteroids.push(rock(200.0, 250.0, LARGE_ASTEROID_SIZE, 240.0));
+        world.update(FRAME);
+        assert_eq!(world.lives, STARTING_LIVES - 1);
+        assert_eq!(world.health, world.config.max_health);
+        let fragments = &world.asteroids[1..];
+        assert_eq!(fragments.len(), 2);
+        assert!(fragments.iter().all(|a| a.size == MEDIUM_ASTEROID_SIZE && a.is_intangible()));
+    }
+
+    #[test]
+    fn test_spread_shot() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.pellets = 5;
+        world.config.spread_angle = 1.0;
+        world.shoot();
+        assert_eq!(world.bullets.len(), 5);
+        let angles: Vec<f64> = world
+            .bullets
+            .iter()
+            .map(|b| b.velocity_x.atan2(-b.velocity_y))
+            .collect();
+        for (angle, expected) in angles.iter().zip([-0.5, -0.25, 0.0, 0.25, 0.5]) {
+            assert!((angle - expected).abs() < 1e-9);
+        }
+
+        // Bursts are truncated at the bullet cap.
+        world.config.max_bullets = 7;
+        world.shot_cooldown = 0.0;
+        world.shoot();
+        assert_eq!(world.bullets.len(), 7);
+        world.shot_cooldown = 0.0;
+        world.shoot();
+        assert_eq!(world.bullets.len(), 7);
+    }
+
+    #[test]
+    fn test_held_input() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.asteroids.clear();
+        world.input = HeldInput {
+            thrust: true,
+            right: true,
+            shoot: true,
+            ..HeldInput::default()
+        };
+        for _ in 0..32 {
+            world.update(1.0 / 64.0);
+        }
+        assert!(world.player.angle > 0.0);
+        assert!(world.player.y < 250.0);
+        assert!(world.player.flame);
+        // Half a second of held fire at one shot per cooldown.
+        assert_eq!(world.bullets.len(), (0.5 / FIRE_COOLDOWN) as usize);
+
+        world.input = HeldInput::default();
+        let angle = world.player.angle;
+        world.update(1.0 / 64.0);
+        assert_eq!(world.player.angle, angle);
+        assert!(!world.player.flame);
+    }
+
+    #[test]
+    fn test_field_bounds() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        world.asteroids.clear();
+        assert_eq!(world.field_bounds(), (0.0, 0.0, 800.0, 600.0));
+
+        world.asteroids = vec![
+            Asteroid::with_size(100.0, 500.0, SMALL_ASTEROID_SIZE, &mut world.rng),
+            Asteroid::with_size(600.0, 350.0, LARGE_ASTEROID_SIZE, &mut world.rng),
+        ];
+        // The ship at (400, 300) sets the top edge.
+        assert_eq!(world.field_bounds(), (90.0, 280.0, 550.0, 230.0));
+    }
+
+    #[test]
+    fn test_validate_reports_non_finite_entities() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        assert_eq!(world.validate(), Ok(()));
+
+        let id = world.asteroids[1].id;
+        world.asteroids[1].velocity_x = f64::NAN;
+        world.player.y = f64::INFINITY;
+        let problems = world.validate().unwrap_err();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0], "player position is (400.0, inf)");
+        let velocity_y = world.asteroids[1].velocity_y;
+        assert_eq!(problems[1], format!("asteroid {id} velocity is (NaN, {velocity_y:?})"));
+    }
+
+    #[test]
+    fn test_aim_assist() {
+        let shoot_near_miss = |assist| {
+            let mut world = World::new(Bounds::new(800.0, 600.0));
+            world.config.aim_assist = assist;
+            world.config.ship_damping = 0.0;
+            // 0.2 radians right of the nose and 150 pixels out, well wide
+            // of a small rock's silhouette. A second rock out of the line of
+            // fire keeps the wave going.
+            let (sin, cos) = 0.2f64.sin_cos();
+            let target = (400.0 + sin * 150.0, 300.0 - cos * 150.0);
+            let mut rocks = Vec::new();
+            for (x, y) in [target, (100.0, 450.0)] {
+                let rock = still_rock(x, y, SMALL_ASTEROID_SIZE, &mut world.rng);
+                rocks.push(rock);
+            }
+            world.asteroids = rocks;
+            world.shoot();
+            world.run_until(FRAME, 60, |w| w.bullets.is_empty());
+            world.wave_stats.shots_hit
+        };
+        assert_eq!(shoot_near_miss(0.0), 0);
+        assert_eq!(shoot_near_miss(1.0), 1);
+    }
+
+    #[test]
+    fn test_lifecycle_events() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.bullet_arming_time = 0.0;
+        world.asteroids.clear();
+        world.track_lifecycle = true;
+        let rock = still_rock(250.0, 200.0, LARGE_ASTEROID_SIZE, &mut world.rng);
+        world.add_asteroid(rock);
+        let far = Asteroid::with_size(50.0, 450.0, SMALL_ASTEROID_SIZE, &mut world.rng);
+        world.add_asteroid(far);
+        let rock_id = world.asteroids[0].id;
+        assert_eq!(world.lifecycle.len(), 2);
+        assert!(world.lifecycle.iter().all(|e| e.change == Lifecycle::Spawned));
+        world.lifecycle.clear();
+
+        world.shoot();
+        world.update(FRAME);
+        let despawned: Vec<_> =
+            world.lifecycle.iter().filter(|e| e.change == Lifecycle::Despawned).collect();
+        assert_eq!(despawned.len(), 1);
+        assert_eq!((despawned[0].id, despawned[0].x, despawned[0].y), (rock_id, 250.0, 200.0));
+        let spawned = world.lifecycle.iter().filter(|e| e.change == Lifecycle::Spawned).count();
+        assert_eq!(spawned, 2);
+
+        world.lifecycle.clear();
+        world.track_lifecycle = false;
+        world.destruction_queue.push_back(world.asteroids[0].id);
+        world.update(FRAME);
+        assert!(world.lifecycle.is_empty());
+    }
+
+    #[test]
+    fn test_fuel() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.asteroids.clear();
+        let rock = Asteroid::with_size(50.0, 450.0, SMALL_ASTEROID_SIZE, &mut world.rng);
+        world.add_asteroid(rock);
+        world.config.max_asteroid_speed = 1.0;
+        world.thrust();
+        assert_eq!(world.fuel, 1.0);
+
+        world.config.fuel_burn_rate = 0.5;
+        world.config.fuel_regen_rate = 0.25;
+        world.input.thrust = true;
+        for _ in 0..8 {
+            world.update(0.25);
         }
+        assert_eq!(world.fuel, 0.0);
+        world.thrust();
+        assert!(!world.player.thrusting);
 
-        // Remove bullets that are off screen
-        self.bullets.retain(|bullet| {
-            bullet.x >= 0.0
-                && bullet.x <= self.canvas.width() as f64
-                && bullet.y >= 0.0
-                && bullet.y <= self.canvas.height() as f64
-        });
+        world.input.thrust = false;
+        for _ in 0..4 {
+            world.update(0.25);
+        }
+        assert_eq!(world.fuel, 0.25);
+        world.thrust();
+        assert!(world.player.thrusting);
 
-        // Check collisions
-        self.check_collisions();
+        world.config.fuel_per_shot = 0.125;
+        world.shoot();
+        assert_eq!(world.fuel, 0.25 - 0.125 - 0.125);
     }
 
-    pub fn render(&self) {
-        // Clear canvas
-        self.context.clear_rect(0.0, 0.0, self.canvas.width() as f64, self.canvas.height() as f64);
-        
-        // Draw player
-        self.player.draw(&self.context);
-        
-        // Draw asteroids
-        for asteroid in &self.asteroids {
-            asteroid.draw(&self.context);
+    #[test]
+    fn test_hyperspace() {
+        let mut world = World::seeded(Bounds::new(800.0, 600.0), 21);
+        world.player.velocity_x = 120.0;
+        world.hyperspace();
+        let landed = (world.player.x, world.player.y);
+        assert_ne!(landed, (400.0, 300.0));
+        assert_eq!((world.player.velocity_x, world.player.velocity_y), (0.0, 0.0));
+        world.hyperspace();
+        assert_eq!((world.player.x, world.player.y), landed);
+
+        for _ in 0..200 {
+            world.hyperspace_cooldown = 0.0;
+            world.hyperspace();
+            assert!((0.0..800.0).contains(&world.player.x));
+            assert!((0.0..600.0).contains(&world.player.y));
         }
-        
-        // Draw bullets
-        for bullet in &self.bullets {
-            bullet.draw(&self.context);
+        assert_eq!(world.lives, STARTING_LIVES);
+
+        world.config.hyperspace_risk = 1.0;
+        world.hyperspace_cooldown = 0.0;
+        world.hyperspace();
+        assert_eq!(world.lives, STARTING_LIVES - 1);
+    }
+
+    #[test]
+    fn test_fire_cooldown() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.asteroids.clear();
+        let rock = Asteroid::with_size(50.0, 450.0, SMALL_ASTEROID_SIZE, &mut world.rng);
+        world.add_asteroid(rock);
+        world.shoot();
+        world.shoot();
+        assert_eq!(world.bullets.len(), 1);
+
+        let mut frames = 0;
+        while world.bullets.len() == 1 {
+            world.update(1.0 / 64.0);
+            world.shoot();
+            frames += 1;
         }
+        assert_eq!(world.bullets.len(), 2);
+        assert_eq!(frames as f64 / 64.0, FIRE_COOLDOWN);
     }
 
-    pub fn shoot(&mut self) {
-        self.bullets.push(self.player.shoot());
+    #[test]
+    fn test_adaptive_difficulty() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        world.config.adaptive_difficulty = true;
+        let clear = |world: &mut World, stats: WaveStats| {
+            world.asteroids.clear();
+            world.wave_stats = stats;
+            world.update(FRAME);
+        };
+
+        clear(&mut world, WaveStats { shots_fired: 10, shots_hit: 10, ..WaveStats::default() });
+        assert!(world.difficulty > 1.0);
+        let raised = world.difficulty;
+
+        clear(&mut world, WaveStats { deaths: 3, ..WaveStats::default() });
+        assert!(world.difficulty < raised);
+        clear(&mut world, WaveStats { deaths: 3, ..WaveStats::default() });
+        assert_eq!(world.difficulty, MIN_DIFFICULTY);
+
+        world.config.adaptive_difficulty = false;
+        clear(&mut world, WaveStats { shots_fired: 10, shots_hit: 10, ..WaveStats::default() });
+        assert_eq!(world.difficulty, MIN_DIFFICULTY);
     }
 
-    pub fn rotate_left(&mut self) {
-        self.player.rotate(-0.1);
+    #[test]
+    fn test_run_until() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.asteroids.clear();
+        world.asteroids.push(Asteroid::new(100.0, 100.0));
+        assert!(world.run_until(0.125, 20, |w| w.wave_stats.elapsed >= 1.25));
+        assert_eq!(world.wave_stats.elapsed, 1.25);
+
+        assert!(!world.run_until(0.125, 5, |w| w.game_over));
+        assert_eq!(world.wave_stats.elapsed, 1.875);
     }
 
-    pub fn rotate_right(&mut self) {
-        self.player.rotate(0.1);
+    #[test]
+    fn test_world_from_level() {
+        let level = Level::parse(
+            "player 100 50 0.25\n\
+             asteroid 300 200 large 1 0\n\
+             asteroid 40 60 small 0 -2\n\
+             wave 0 3 0",
+        )
+        .unwrap();
+        let mut world = World::from_level(Bounds::new(800.0, 600.0), &level);
+        assert_eq!((world.player.x, world.player.y, world.player.angle), (100.0, 50.0, 0.25));
+        let asteroids: Vec<_> = world
+            .asteroids
+            .iter()
+            .map(|a| (a.x, a.y, a.size, a.velocity_x, a.velocity_y))
+            .collect();
+        assert_eq!(
+            asteroids,
+            vec![
+                (300.0, 200.0, LARGE_ASTEROID_SIZE, 1.0, 0.0),
+                (40.0, 60.0, SMALL_ASTEROID_SIZE, 0.0, -2.0),
+            ]
+        );
+
+        world.asteroids.clear();
+        world.update(FRAME);
+        assert_eq!(world.wave, 2);
+        assert_eq!(world.asteroids.len(), 3);
+        assert!(world.asteroids.iter().all(|a| a.size == MEDIUM_ASTEROID_SIZE));
     }
 
-    pub fn thrust(&mut self) {
-        self.player.thrust();
+    #[test]
+    fn test_spawn_wave_counts() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        assert_eq!(world.asteroids.len(), 5);
+
+        world.asteroids.clear();
+        world.spawn_wave(WaveSpec { large: 2, medium: 3, small: 1, ..WaveSpec::default() });
+        let count = |size: f64| world.asteroids.iter().filter(|a| a.size == size).count();
+        assert_eq!(count(LARGE_ASTEROID_SIZE), 2);
+        assert_eq!(count(MEDIUM_ASTEROID_SIZE), 3);
+        assert_eq!(count(SMALL_ASTEROID_SIZE), 1);
+        assert_eq!(world.asteroids.len(), 6);
     }
 
-    fn check_collisions(&mut self) {
-        // Check bullet-asteroid collisions
-        let mut i = 0;
-        while i < self.bullets.len() {
-            let mut j = 0;
-            while j < self.asteroids.len() {
-                if self.bullets[i].collides_with(&self.asteroids[j]) {
-                    self.bullets.remove(i);
-                    self.asteroids.remove(j);
-                    self.score += 100;
-                    break;
-                }
-                j += 1;
-            }
-            if i < self.bullets.len() {
-                i += 1;
-            }
+    #[test]
+    fn test_wave_intro_stagger() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        world.asteroids.clear();
+        // Steps of 1/64 s keep the countdown exact.
+        let step = 1.0 / 64.0;
+        world.config.wave_intro_interval = 10.0 * step;
+        world.spawn_wave(WaveSpec { large: 4, ..WaveSpec::default() });
+        assert_eq!(world.asteroids.len(), 1);
+
+        let mut counts = Vec::new();
+        for _ in 0..40 {
+            world.update(step);
+            counts.push(world.asteroids.len());
         }
+        assert_eq!(counts[8], 1);
+        assert_eq!(counts[9], 2);
+        assert_eq!(counts[19], 3);
+        assert_eq!(counts[29], 4);
+        assert!(counts.windows(2).all(|w| w[1] - w[0] <= 1));
+        assert_eq!(counts[39], 4);
+        assert!(world.pending_spawns.is_empty());
     }
-}
 
-struct Player {
-    x: f64,
-    y: f64,
-    angle: f64,
-    velocity_x: f64,
-    velocity_y: f64,
-}
+    #[test]
+    fn test_level_progression() {
+        let mut world = World::seeded(Bounds::new(800.0, 600.0), 21);
+        assert_eq!(world.wave, 1);
+        assert_eq!(world.asteroids.len(), 5);
+        let total_speed = |world: &World| -> f64 {
+            world.asteroids.iter().map(|a| a.velocity_x.hypot(a.velocity_y)).sum()
+        };
+        let first_speed = total_speed(&world);
 
-impl Player {
-    fn new(x: f64, y: f64) -> Player {
-        Player {
-            x,
-            y,
-            angle: 0.0,
-            velocity_x: 0.0,
-            velocity_y: 0.0,
+        world.asteroids.clear();
+        world.update(FRAME);
+        assert_eq!(world.wave, 2);
+        assert_eq!(world.asteroids.len(), 6);
+
+        // Re-rolling the opening wave with wave 2's speedup shows the same
+        // rocks, only faster.
+        let mut faster = World::empty(Bounds::new(800.0, 600.0), 21);
+        faster.wave = 2;
+        faster.spawn_wave(wave::spec_for(&faster.waves, 0));
+        let expected = first_speed * (1.0 + faster.config.wave_speedup);
+        assert!((total_speed(&faster) - expected).abs() < 1e-9);
+
+        let mut crowded = World::empty(Bounds::new(800.0, 600.0), 4);
+        let ship = (crowded.player.x, crowded.player.y);
+        crowded.spawn_wave(WaveSpec { small: 200, ..WaveSpec::default() });
+        for a in &crowded.asteroids {
+            assert!(toroidal_distance(ship, (a.x, a.y), crowded.bounds) >= SPAWN_CLEARANCE);
         }
     }
 
-    fn update(&mut self, (width, height): (f64, f64)) {
-        self.x += self.velocity_x;
-        self.y += self.velocity_y;
+    #[test]
+    fn test_spawn_safe_cone() {
+        let mut world = World::seeded(Bounds::new(800.0, 600.0), 3);
+        world.asteroids.clear();
+        world.player.angle = 1.0;
+        world.config.spawn_safe_cone = 0.2;
+        world.spawn_wave(WaveSpec { medium: 50, ..WaveSpec::default() });
+        assert_eq!(world.asteroids.len(), 50);
 
-        let size = 20.0;
+        let ship = (world.player.x, world.player.y);
+        let facing = (world.player.angle.sin(), -world.player.angle.cos());
+        for a in &world.asteroids {
+            let (dx, dy) = toroidal_delta((a.x, a.y), ship, world.bounds);
+            let cos = (dx * facing.0 + dy * facing.1) / dx.hypot(dy);
+            assert!(cos < 0.2f64.cos());
+        }
+    }
 
-        if self.x > width + size { self.x -= width + size + size }
-        if self.x < 0.0 - size { self.x += width + size + size }
+    #[test]
+    fn test_edge_spawn_heads_inward() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        world.asteroids.clear();
+        world.config.edge_spawn_spread = 0.3;
+        world.config.edge_entry_speed = 120.0;
+        world.spawn_wave(WaveSpec { medium: 40, from_edges: true, ..WaveSpec::default() });
+        assert_eq!(world.asteroids.len(), 40);
 
-        if self.y > height + size { self.y -= height + size + size }
-        if self.y < 0.0 - size { self.y += height  + size + size }
+        for a in &world.asteroids {
+            let normal = if a.x == 0.0 {
+                (1.0, 0.0)
+            } else if a.x == 800.0 {
+                (-1.0, 0.0)
+            } else if a.y == 0.0 {
+                (0.0, 1.0)
+            } else {
+                assert_eq!(a.y, 600.0);
+                (0.0, -1.0)
+            };
+            let speed = a.velocity_x.hypot(a.velocity_y);
+            let inward = a.velocity_x * normal.0 + a.velocity_y * normal.1;
+            assert!((speed - 120.0).abs() < 1e-9);
+            assert!(inward > 0.0);
+            assert!((inward / speed).acos() <= 0.3 + 1e-9);
+        }
     }
 
-    fn draw(&self, context: &CanvasRenderingContext2d) {
-        context.save();
-        context.translate(self.x, self.y).unwrap();
-        context.rotate(self.angle).unwrap();
-        
-        context.begin_path();
-        context.move_to(0.0, -20.0);
-        context.line_to(10.0, 10.0);
-        context.line_to(-10.0, 10.0);
-        context.close_path();
-        
-        context.set_stroke_style_str("white");
-        context.stroke();
-        
-        context.restore();
+    #[test]
+    fn test_wave_clear_bonus() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        world.asteroids.clear();
+        world.wave_stats = WaveStats {
+            elapsed: 0.0,
+            shots_fired: 4,
+            shots_hit: 4,
+            deaths: 0,
+        };
+        world.update(FRAME);
+
+        assert_eq!(world.wave, 2);
+        assert_eq!(world.wave_stats, WaveStats::default());
+        let spawned: u32 = wave::spec_for(&world.waves, 1).asteroids().iter().map(|(_, n)| n).sum();
+        assert_eq!(world.asteroids.len(), spawned as usize);
+        let Some(GameEvent::WaveCleared {
+            wave,
+            time_bonus,
+            accuracy_bonus,
+        }) = world.events.back().cloned()
+        else {
+            panic!("expected a WaveCleared event");
+        };
+        assert_eq!(wave, 1);
+        assert_eq!(accuracy_bonus, wave::MAX_ACCURACY_BONUS);
+        assert_eq!(world.score, time_bonus + accuracy_bonus);
     }
 
-    fn rotate(&mut self, angle: f64) {
-        self.angle += angle;
+    #[test]
+    fn test_score_summary() {
+        let mut world = World::seeded(Bounds::new(800.0, 600.0), 1234);
+        world.asteroids.clear();
+        let rock = still_rock(400.0, 200.0, SMALL_ASTEROID_SIZE, &mut world.rng);
+        world.add_asteroid(rock);
+        world.shoot();
+        assert!(world.run_until(1.0 / 64.0, 64, |w| w.wave == 2));
+        let cleared = world.played;
+        let (time_bonus, accuracy_bonus) = cleared.clear_bonus();
+
+        // A miss in the next wave counts against the run's accuracy.
+        world.shot_cooldown = 0.0;
+        world.player.angle = PI;
+        world.shoot();
+        let summary = world.score_summary();
+        assert_eq!(summary.score, SMALL_ASTEROID_POINTS + time_bonus + accuracy_bonus);
+        assert_eq!(summary.wave, 2);
+        assert_eq!(summary.accuracy, 0.5);
+        assert_eq!(summary.time_survived, cleared.elapsed);
+        assert_eq!(summary.seed, 1234);
+        let json = serde_json::to_string(&summary).unwrap();
+        assert!(json.contains(r#""seed":"1234""#));
     }
 
-    fn thrust(&mut self) {
-        let (sin, cos) = self.angle.sin_cos();
-        self.velocity_x += sin * 0.5;
-        self.velocity_y -= cos * 0.5;
+    #[test]
+    fn test_continue_game() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.continue_game();
+        assert_eq!(world.continues, 0);
+
+        world.config.continue_lives = 2;
+        world.config.continue_score_factor = 0.25;
+        world.score = 1000;
+        world.lives = 0;
+        world.game_over = true;
+        world.continue_game();
+        assert!(!world.game_over);
+        assert_eq!(world.lives, 2);
+        assert_eq!(world.score, 250);
+        assert_eq!(world.continues, 1);
     }
 
-    fn shoot(&self) -> Bullet {
-        let (sin, cos) = self.angle.sin_cos();
-        Bullet {
-            x: self.x + sin * 20.0,
-            y: self.y - cos * 20.0,
-            velocity_x: sin * 10.0 + self.velocity_x,
-            velocity_y: -cos * 10.0 + self.velocity_y,
+    #[test]
+    fn test_json_round_trip() {
+        let mut world = World::seeded(Bounds::new(800.0, 600.0), 4);
+        world.config.bullet_arming_time = 0.0;
+        world.input.thrust = true;
+        world.shoot();
+        for _ in 0..10 {
+            world.update(FRAME);
         }
+        world.score = 700;
+        let saved = world.to_json();
+
+        let mut loaded = World::seeded(Bounds::new(800.0, 600.0), 99);
+        loaded.load_json(&saved).unwrap();
+        assert_eq!(loaded.to_json(), saved);
+        assert_eq!(loaded.score, 700);
+        assert_eq!((loaded.player.x, loaded.player.y), (world.player.x, world.player.y));
+        assert_eq!(loaded.bullets.len(), world.bullets.len());
+        assert!(loaded.asteroids.iter().all(|a| a.id < loaded.next_asteroid_id));
+
+        assert!(loaded.load_json("{\"score\": 5").is_err());
+        assert!(loaded.load_json("[]").is_err());
+        assert_eq!(loaded.to_json(), saved);
     }
-}
 
-struct Asteroid {
-    x: f64,
-    y: f64,
-    velocity_x: f64,
-    velocity_y: f64,
-    size: f64,
-}
+    #[test]
+    fn test_respawn_invulnerability() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.asteroids.clear();
+        let rock = still_rock(250.0, 250.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        world.asteroids.push(rock);
 
-impl Asteroid {
-    fn new(x: f64, y: f64) -> Asteroid {
-        Asteroid {
-            x,
-            y,
-            velocity_x: (rand::random::<f64>() - 0.5) * 2.0,
-            velocity_y: (rand::random::<f64>() - 0.5) * 2.0,
-            size: 20.0,
+        world.lose_life();
+        assert_eq!(world.player.invulnerable, RESPAWN_INVULNERABILITY);
+        // The window runs out during the eighth quarter second.
+        for _ in 0..7 {
+            world.update(0.25);
+            assert_eq!(world.lives, STARTING_LIVES - 1);
         }
+        world.update(0.25);
+        assert_eq!(world.lives, STARTING_LIVES - 2);
     }
 
-    fn update(&mut self, (width, height): (f64, f64)) {
-        self.x += self.velocity_x;
-        self.y += self.velocity_y;
+    #[test]
+    fn test_resize_clamps_entities() {
+        let mut world = World::new(Bounds::new(1000.0, 700.0));
+        world.asteroids.clear();
+        let mut rock = Asteroid::new(900.0, 650.0);
+        (rock.velocity_x, rock.velocity_y) = (0.0, 0.0);
+        world.add_asteroid(rock);
+        world.player.x = 950.0;
 
-        let size = self.size;
+        world.resize(Bounds::new(800.0, 600.0));
+        assert_eq!(world.bounds, Bounds::new(800.0, 600.0));
+        assert_eq!((world.asteroids[0].x, world.asteroids[0].y), (800.0, 600.0));
+        assert_eq!(world.player.x, 800.0);
+        world.update(FRAME);
+        assert!(world.asteroids[0].x <= 800.0 + world.config.wrap_buffer);
+        assert!(world.validate().is_ok());
+    }
 
-        if self.x > width + size { self.x -= width + size + size }
-        if self.x < 0.0 - size { self.x += width + size + size }
+    #[test]
+    fn test_restart() {
+        let mut world = World::seeded(Bounds::new(500.0, 500.0), 9);
+        world.config.ship_damping = 0.0;
+        world.shoot();
+        world.player.velocity_x = 50.0;
+        world.update(FRAME);
+        world.asteroids.truncate(1);
+        (world.score, world.lives, world.wave, world.game_over) = (1200, 0, 4, true);
+        world.wave_stats.shots_fired = 7;
+        world.continues = 2;
 
-        if self.y > height + size { self.y -= height + size + size }
-        if self.y < 0.0 - size { self.y += height  + size + size }
+        world.restart();
+        assert_eq!((world.score, world.lives, world.wave), (0, STARTING_LIVES, 1));
+        assert!(!world.game_over && !world.paused);
+        assert_eq!(world.continues, 0);
+        assert!(world.bullets.is_empty());
+        assert_eq!(world.wave_stats, WaveStats::default());
+        assert_eq!((world.player.x, world.player.y), (250.0, 250.0));
+        assert_eq!((world.player.velocity_x, world.player.velocity_y), (0.0, 0.0));
+        assert_eq!(world.asteroids.len(), World::seeded(world.bounds, 1).asteroids.len());
+        assert!(world.history.is_empty());
+        assert_eq!(world.config.ship_damping, 0.0);
     }
 
-    fn draw(&self, context: &CanvasRenderingContext2d) {
-        context.begin_path();
-        context.arc(self.x, self.y, self.size, 0.0, TAU).unwrap();
-        context.set_stroke_style_str("white");
-        context.stroke();
+    #[test]
+    fn test_respawn_strategies() {
+        let mut world = World::seeded(Bounds::new(800.0, 600.0), 9);
+        let rock = still_rock(100.0, 100.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        world.asteroids = vec![rock.clone(), rock];
+        world.asteroids[1].x = 700.0;
+
+        assert_eq!(world.respawn_point(), (400.0, 300.0));
+
+        world.config.respawn_strategy = RespawnStrategy::SafestPoint;
+        let safest = world.respawn_point();
+        let best = world.clearance(safest);
+        for x in (0..800).step_by(10) {
+            for y in (0..600).step_by(10) {
+                // The grid is coarser than this scan, so allow a cell's slack.
+                assert!(world.clearance((x as f64, y as f64)) <= best + 30.0);
+            }
+        }
+        assert!(best > world.clearance((400.0, 100.0)));
+
+        world.config.respawn_strategy = RespawnStrategy::LastSafePosition;
+        world.player.x = 400.0;
+        world.player.y = 400.0;
+        world.update(FRAME);
+        world.player.x = 110.0;
+        world.player.y = 100.0;
+        world.update(FRAME);
+        assert_eq!(world.respawn_point(), (400.0, 400.0));
     }
-}
 
-struct Bullet {
-    x: f64,
-    y: f64,
-    velocity_x: f64,
-    velocity_y: f64,
-}
+    #[test]
+    fn test_near_miss() {
+        let mut world = World::seeded(Bounds::new(500.0, 500.0), 10);
+        world.config.near_miss_scoring = true;
+        // Touching distance is 40; this rock passes at 45, inside the band.
+        let mut rock = still_rock(150.0, 205.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        rock.velocity_x = 240.0;
+        world.asteroids = vec![rock];
+        for _ in 0..50 {
+            world.update(FRAME);
+        }
+        assert_eq!(world.score, NEAR_MISS_BONUS);
+        assert_eq!(world.asteroids.len(), 1);
 
-impl Bullet {
-    fn update(&mut self) {
-        self.x += self.velocity_x;
-        self.y += self.velocity_y;
+        world.config.near_miss_scoring = false;
+        world.asteroids[0].grazed = false;
+        world.asteroids[0].x = 250.0;
+        world.update(FRAME);
+        assert_eq!(world.score, NEAR_MISS_BONUS);
     }
 
-    fn draw(&self, context: &CanvasRenderingContext2d) {
-        context.begin_path();
-        context.arc(self.x, self.y, 2.0, 0.0, TAU).unwrap();
-        context.set_fill_style_str("white");
-        context.fill();
+    #[test]
+    fn test_bullet_fired_event() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.shoot();
+        let bullet = &world.bullets[0];
+        assert_eq!(
+            world.events,
+            vec![GameEvent::BulletFired {
+                position: (bullet.x, bullet.y),
+                angle: 0.0,
+            }]
+        );
+
+        world.events.clear();
+        world.config.max_bullets = 1;
+        world.shoot();
+        assert_eq!(world.bullets.len(), 1);
+        assert!(world.events.is_empty());
     }
 
-    fn collides_with(&self, asteroid: &Asteroid) -> bool {
-        let dx = self.x - asteroid.x;
-        let dy = self.y - asteroid.y;
-        let distance_sq = dx * dx + dy * dy;
-        distance_sq < asteroid.size * asteroid.size
+    #[test]
+    fn test_event_log_cap() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.event_log_cap = 3;
+        for wave in 1..=5 {
+            world.push_event(GameEvent::WaveCleared { wave, time_bonus: 0, accuracy_bonus: 0 });
+            assert!(world.events.len() <= 3);
+        }
+        let waves = |world: &World| -> Vec<u32> {
+            world
+                .events
+                .iter()
+                .filter_map(|e| match e {
+                    GameEvent::WaveCleared { wave, .. } => Some(*wave),
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!(waves(&world), vec![3, 4, 5]);
+
+        world.events.clear();
+        world.config.event_overflow = EventOverflow::DropNewest;
+        for wave in 1..=5 {
+            world.push_event(GameEvent::WaveCleared { wave, time_bonus: 0, accuracy_bonus: 0 });
+        }
+        assert_eq!(waves(&world), vec![1, 2, 3]);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_danger_level() {
+        let mut world = World::seeded(Bounds::new(800.0, 600.0), 11);
+        world.player.x = 10.0;
+        world.player.y = 300.0;
+        let rocks = |world: &mut World, spots: &[(f64, f64)]| {
+            let rocks = spots
+                .iter()
+                .map(|&(x, y)| still_rock(x, y, MEDIUM_ASTEROID_SIZE, &mut world.rng))
+                .collect();
+            world.asteroids = rocks;
+        };
+
+        rocks(&mut world, &[(400.0, 300.0), (450.0, 100.0)]);
+        let sparse = world.danger_level();
+
+        // Across the left edge from the ship, so only close on the torus.
+        rocks(&mut world, &[(780.0, 300.0), (790.0, 320.0), (40.0, 280.0)]);
+        let clustered = world.danger_level();
+
+        assert_eq!(sparse, 0.0);
+        assert!(clustered > sparse);
+        assert!(clustered <= 1.0);
+    }
 
     #[test]
-    fn test_player_creation() {
-        let player = Player::new(100.0, 100.0);
-        assert_eq!(player.x, 100.0);
-        assert_eq!(player.y, 100.0);
-        assert_eq!(player.angle, 0.0);
-        assert_eq!(player.velocity_x, 0.0);
-        assert_eq!(player.velocity_y, 0.0);
+    fn test_rewind() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.player.velocity_x = 60.0;
+        world.update(FRAME);
+        world.update(FRAME);
+
+        let player = (world.player.x, world.player.y);
+        let asteroids: Vec<_> = world.asteroids.iter().map(|a| (a.x, a.y)).collect();
+
+        for _ in 0..5 {
+            world.update(FRAME);
+        }
+        assert_ne!(world.player.x, player.0);
+
+        assert_eq!(world.rewind(5), 5);
+        assert_eq!((world.player.x, world.player.y), player);
+        let restored: Vec<_> = world.asteroids.iter().map(|a| (a.x, a.y)).collect();
+        assert_eq!(restored, asteroids);
+
+        assert_eq!(world.rewind(10), 2);
+        assert_eq!(world.rewind(1), 0);
     }
 
     #[test]
-    fn test_player_rotation() {
-        let mut player = Player::new(100.0, 100.0);
-        player.rotate(0.5);
-        assert!((player.angle - 0.5).abs() < 0.0001);
+    fn test_frame_summary() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.score = 300;
+        let summaries: Vec<FrameSummary> = (0..3).map(|_| world.update(FRAME)).collect();
+        assert_eq!(summaries.len(), 3);
+        for summary in summaries {
+            assert_eq!(
+                summary,
+                FrameSummary {
+                    score: 300,
+                    lives: STARTING_LIVES,
+                    wave: 1,
+                    asteroids: 5,
+                }
+            );
+        }
+        assert_eq!(
+            serde_json::to_string(&world.summary()).unwrap(),
+            r#"{"score":300,"lives":3,"wave":1,"asteroids":5}"#
+        );
     }
 
     #[test]
-    fn test_player_thrust() {
-        let mut player = Player::new(100.0, 100.0);
-        player.thrust();
-        assert!(player.velocity_x != 0.0 || player.velocity_y != 0.0);
+    fn test_entities_in_rect() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        world.asteroids = vec![Asteroid::new(100.0, 100.0), Asteroid::new(790.0, 300.0)];
+
+        let found = world.entities_in_rect((80.0, 80.0, 40.0, 40.0));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, EntityKind::Asteroid);
+        assert_eq!(found[0].index, 0);
+
+        // Hangs off the left edge and catches the rock near the right edge.
+        let found = world.entities_in_rect((-30.0, 280.0, 40.0, 40.0));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].index, 1);
+
+        assert!(world.entities_in_rect((550.0, 450.0, 50.0, 50.0)).is_empty());
+
+        world.thrust();
+        let ship = world.entities_in_rect((390.0, 290.0, 20.0, 20.0));
+        assert_eq!(ship[0].kind, EntityKind::Player);
+        assert_ne!((ship[0].velocity_x, ship[0].velocity_y), (0.0, 0.0));
+        assert_eq!(
+            (ship[0].velocity_x, ship[0].velocity_y),
+            (world.player.velocity_x, world.player.velocity_y)
+        );
     }
 
     #[test]
-    fn test_player_movement() {
-        let mut player = Player::new(100.0, 100.0);
-        let initial_x = player.x;
-        let initial_y = player.y;
-        player.velocity_x = 1.0;
-        player.velocity_y = 1.0;
-        player.update((500.0, 500.0));
-        assert_eq!(player.x, initial_x + 1.0);
-        assert_eq!(player.y, initial_y + 1.0);
+    fn test_queued_destruction() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        let ids: Vec<u32> = world.asteroids.iter().map(|a| a.id).collect();
+        world.destruction_queue.extend([ids[3], 999, ids[1]]);
+
+        world.update(FRAME);
+        assert_eq!(world.asteroids.len(), 4);
+        assert!(world.asteroids.iter().all(|a| a.id != ids[3]));
+        assert_eq!(world.shockwaves.len(), 1);
+
+        world.update(FRAME);
+        assert_eq!(world.asteroids.len(), 3);
+        assert!(world.asteroids.iter().all(|a| a.id != ids[1]));
+        assert!(world.destruction_queue.is_empty());
+        assert_eq!(world.score, 0);
     }
 
     #[test]
-    fn test_asteroid_creation() {
-        let asteroid = Asteroid::new(100.0, 100.0);
-        assert_eq!(asteroid.x, 100.0);
-        assert_eq!(asteroid.y, 100.0);
-        assert_eq!(asteroid.size, 20.0);
-        assert!(asteroid.velocity_x.abs() <= 1.0);
-        assert!(asteroid.velocity_y.abs() <= 1.0);
+    fn test_bullet_count_telemetry() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        // The replacement wave is still intangible while the shots fly.
+        world.asteroids.clear();
+        world.shoot();
+        world.shot_cooldown = 0.0;
+        world.shoot();
+        assert_eq!(world.bullets.len(), 2);
+        assert!(world.bullets.capacity() >= 2);
+
+        // Bullets wrap rather than leaving, and fizzle out after a second.
+        for _ in 0..30 {
+            world.update(FRAME);
+        }
+        assert_eq!(world.bullets.len(), 2);
+        assert!(world.bullets.iter().all(|b| b.y > 0.0 && b.y < 500.0));
+        for _ in 0..31 {
+            world.update(FRAME);
+        }
+        assert_eq!(world.bullets.len(), 0);
     }
 
     #[test]
-    fn test_asteroid_movement() {
-        let mut asteroid = Asteroid::new(100.0, 100.0);
-        let initial_x = asteroid.x;
-        let initial_y = asteroid.y;
-        asteroid.update((500.0, 500.0));
-        assert!(asteroid.x != initial_x || asteroid.y != initial_y);
+    fn test_bullet_arming() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.bullet_arming_time = 0.03125;
+        let rock = still_rock(250.0, 220.0, MEDIUM_ASTEROID_SIZE, &mut world.rng);
+        world.asteroids = vec![rock];
+        // The muzzle sits 20 pixels ahead of the ship, inside the rock.
+        world.shoot();
+        world.update(1.0 / 64.0);
+        assert_eq!(world.bullets.len(), 1);
+        assert_eq!(world.asteroids.len(), 1);
+
+        world.update(1.0 / 64.0);
+        assert!(world.bullets.is_empty());
+        assert_eq!(world.asteroids.len(), 2);
     }
 
     #[test]
-    fn test_bullet_creation() {
-        let player = Player::new(100.0, 100.0);
-        let bullet = player.shoot();
-        assert!(bullet.x > 0.0);
-        assert!(bullet.y > 0.0);
-        assert!(bullet.velocity_x != 0.0 || bullet.velocity_y != 0.0);
+    fn test_bullet_interception() {
+        let bullet = |x: f64, owner| Bullet { owner, ..player_bullet(x, 50.0, 0.0, 0.0) };
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.config.bullet_arming_time = 0.0;
+        world.asteroids.clear();
+
+        world.bullets = vec![bullet(50.0, BulletOwner::Player), bullet(51.0, BulletOwner::Enemy)];
+        world.check_collisions();
+        assert_eq!(world.bullets.len(), 2);
+
+        world.config.bullet_interception = true;
+        world.bullets.push(bullet(300.0, BulletOwner::Enemy));
+        world.check_collisions();
+        assert_eq!(world.bullets.len(), 1);
+        assert_eq!(world.bullets[0].x, 300.0);
     }
 
     #[test]
-    fn test_bullet_movement() {
-        let mut bullet = Bullet {
-            x: 100.0,
-            y: 100.0,
-            velocity_x: 1.0,
-            velocity_y: 1.0,
-        };
-        let initial_x = bullet.x;
-        let initial_y = bullet.y;
-        bullet.update();
-        assert_eq!(bullet.x, initial_x + 1.0);
-        assert_eq!(bullet.y, initial_y + 1.0);
+    fn test_ufo_flies_in_and_fires() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        world.asteroids.clear();
+        let rock = Asteroid::with_size(50.0, 550.0, SMALL_ASTEROID_SIZE, &mut world.rng);
+        world.add_asteroid(rock);
+        world.config.max_asteroid_speed = 1.0;
+        world.ufo_timer = 0.25;
+        world.update(0.25);
+        assert_eq!(world.ufos.len(), 1);
+        assert!(world.ufos[0].x < UFO_RADIUS || world.ufos[0].x > 800.0 - UFO_RADIUS);
+
+        let is_enemy = |b: &Bullet| b.owner == BulletOwner::Enemy;
+        // The saucer arrived at the start of the first update.
+        let mut flown = 0.25;
+        while !world.bullets.iter().any(is_enemy) {
+            world.update(0.25);
+            flown += 0.25;
+        }
+        assert_eq!(flown, UFO_FIRE_INTERVAL);
+        let shot = world.bullets.iter().find(|b| is_enemy(b)).unwrap();
+        let (dx, dy) = toroidal_delta((400.0, 300.0), (shot.x, shot.y), world.bounds);
+        assert!(dx * shot.velocity_x + dy * shot.velocity_y > 0.0);
+        assert!((dx * shot.velocity_y - dy * shot.velocity_x).abs() < 1e-6 * dx.hypot(dy) * 300.0);
+
+        world.config.ufos_enabled = false;
+        world.update(FRAME);
+        assert!(world.ufos.is_empty());
     }
 
     #[test]
-    fn test_collision_detection() {
-        let bullet = Bullet {
-            x: 100.0,
-            y: 100.0,
-            velocity_x: 0.0,
-            velocity_y: 0.0,
-        };
-        let asteroid = Asteroid {
-            x: 100.0,
+    fn test_ufo_collisions() {
+        let mut world = World::new(Bounds::new(800.0, 600.0));
+        world.config.bullet_arming_time = 0.0;
+        world.asteroids.clear();
+        let still = |x, y, owner| Bullet { owner, ..player_bullet(x, y, 0.0, 0.0) };
+        world.ufos.push(Ufo {
+            x: 400.0,
             y: 100.0,
             velocity_x: 0.0,
             velocity_y: 0.0,
-            size: 20.0,
+            fire_timer: UFO_FIRE_INTERVAL,
+        });
+        world.bullets = vec![still(405.0, 100.0, BulletOwner::Player)];
+        world.check_collisions();
+        assert!(world.ufos.is_empty());
+        assert!(world.bullets.is_empty());
+        assert_eq!(world.score, UFO_POINTS);
+
+        world.bullets = vec![still(400.0, 300.0, BulletOwner::Enemy)];
+        world.check_collisions();
+        assert!(world.bullets.is_empty());
+        assert_eq!(world.lives, STARTING_LIVES - 1);
+    }
+
+    #[test]
+    fn test_toggle_pause_freezes_cooldown() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        world.shoot();
+        assert_eq!(world.shot_cooldown, FIRE_COOLDOWN);
+        let position = world.bullets[0].y;
+
+        world.toggle_pause();
+        assert!(world.paused);
+        world.update(0.25);
+        assert_eq!(world.shot_cooldown, FIRE_COOLDOWN);
+        assert_eq!(world.bullets[0].y, position);
+
+        world.toggle_pause();
+        assert!(!world.paused);
+        world.update(0.125);
+        assert_eq!(world.shot_cooldown, FIRE_COOLDOWN - 0.125);
+    }
+
+    #[test]
+    fn test_capture_cadence() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        assert_eq!(world.advance_capture(), None);
+
+        world.capture_interval = 3;
+        let captured: Vec<u64> = (0..10).filter_map(|_| world.advance_capture()).collect();
+        assert_eq!(captured, vec![0, 3, 6, 9]);
+    }
+
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_capture_frame() {
+        let world = World::new(Bounds::new(500.0, 500.0));
+        let surface = Recorder::default();
+        let url = world.capture_frame(&surface).unwrap();
+        assert!(url.starts_with("data:image/png"));
+        let calls = surface.calls.into_inner();
+        assert_eq!(calls.first().map(String::as_str), Some("clear_rect(0, 0, 500, 500)"));
+        assert_eq!(calls.last().map(String::as_str), Some("to_data_url"));
+
+        let lost = Recorder {
+            lost: true,
+            ..Recorder::default()
         };
-        assert!(bullet.collides_with(&asteroid));
+        assert!(world.capture_frame(&lost).is_err());
+    }
 
-        let bullet = Bullet {
-            x: 150.0,
-            y: 150.0,
-            velocity_x: 0.0,
-            velocity_y: 0.0,
+    #[test]
+    #[cfg(feature = "web")]
+    fn test_context_lost_pauses() {
+        let mut world = World::new(Bounds::new(500.0, 500.0));
+        let surface = Recorder {
+            lost: true,
+            ..Recorder::default()
         };
-        assert!(!bullet.collides_with(&asteroid));
+        world.config.ship_damping = 0.0;
+        world.tick(&surface, FRAME);
+        assert!(world.paused);
+        assert_eq!(world.events.back(), Some(&GameEvent::ContextLost));
+
+        let frozen = world.player.y;
+        world.player.velocity_y = 4.0;
+        world.tick(&surface, 0.25);
+        assert_eq!(world.player.y, frozen);
+        assert_eq!(world.events.iter().filter(|e| **e == GameEvent::ContextLost).count(), 1);
+
+        world.recover_context();
+        world.tick(&Recorder::default(), 0.25);
+        assert!(!world.paused);
+        assert_eq!(world.player.y, frozen + 1.0);
     }
 
     #[test]
@@ -395,12 +6195,11 @@ mod tests {
             Asteroid::new(300.0, 300.0),
         ];
         let mut bullets = Vec::new();
-        let mut score = 0;
-        let canvas_dimensions = (500.0, 500.0);
+        let canvas_dimensions = Bounds::new(500.0, 500.0);
 
         // Test shooting
         let initial_bullet_count = bullets.len();
-        bullets.push(player.shoot());
+        bullets.push(player.shoot(0.0));
         assert_eq!(bullets.len(), initial_bullet_count + 1);
 
         // Test rotation
@@ -413,21 +6212,21 @@ mod tests {
         // Test thrust
         let initial_velocity_x = player.velocity_x;
         let initial_velocity_y = player.velocity_y;
-        player.thrust();
+        player.thrust(&GameConfig::default(), FRAME);
         assert!(player.velocity_x != initial_velocity_x || 
                 player.velocity_y != initial_velocity_y);
 
         // Test movement
         let initial_x = player.x;
         let initial_y = player.y;
-        player.update(canvas_dimensions);
+        player.update(canvas_dimensions, &GameConfig::default(), FRAME);
         assert!(player.x != initial_x || player.y != initial_y);
 
         // Test asteroid movement
         for asteroid in &mut asteroids {
             let initial_x = asteroid.x;
             let initial_y = asteroid.y;
-            asteroid.update(canvas_dimensions);
+            asteroid.update(canvas_dimensions, &GameConfig::default(), FRAME);
             assert!(asteroid.x != initial_x || asteroid.y != initial_y);
         }
 
@@ -435,18 +6234,13 @@ mod tests {
         for bullet in &mut bullets {
             let initial_x = bullet.x;
             let initial_y = bullet.y;
-            bullet.update();
+            bullet.update(canvas_dimensions, &GameConfig::default(), FRAME);
             assert!(bullet.x != initial_x || bullet.y != initial_y);
         }
 
         // Test collision detection
-        let bullet = Bullet {
-            x: 100.0,
-            y: 100.0,
-            velocity_x: 0.0,
-            velocity_y: 0.0,
-        };
+        let bullet = player_bullet(100.0, 100.0, 0.0, 0.0);
         let asteroid = &asteroids[0];
-        assert!(bullet.collides_with(asteroid));
+        assert!(bullet.collides_with(asteroid, canvas_dimensions));
     }
 }
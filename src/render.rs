@@ -0,0 +1,287 @@
+#[cfg(feature = "web")]
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "web")]
+use web_sys::CanvasRenderingContext2d;
+
+/// Whether ship and asteroid outlines are drawn hollow or solid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderStyle {
+    #[default]
+    Wireframe,
+    Filled,
+}
+
+impl RenderStyle {
+    pub fn from_name(name: &str) -> Option<RenderStyle> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "wireframe" => Some(RenderStyle::Wireframe),
+            "filled" => Some(RenderStyle::Filled),
+            _ => None,
+        }
+    }
+}
+
+/// Strokes the current path in `color`, filling it first in `Filled` style.
+#[cfg(feature = "web")]
+pub fn paint(surface: &impl Surface, color: &str, style: RenderStyle) {
+    if style == RenderStyle::Filled {
+        surface.set_fill_style(color);
+        surface.fill();
+    }
+    surface.set_stroke_style(color);
+    surface.stroke();
+}
+
+/// The subset of the 2D canvas API the game draws with. Implemented for the
+/// real `CanvasRenderingContext2d`, and by a recorder in tests so rendering
+/// can be exercised without a browser.
+#[cfg(feature = "web")]
+pub trait Surface {
+    fn save(&self);
+    fn restore(&self);
+    fn translate(&self, x: f64, y: f64) -> Result<(), JsValue>;
+    fn rotate(&self, angle: f64) -> Result<(), JsValue>;
+    fn scale(&self, x: f64, y: f64) -> Result<(), JsValue>;
+    fn begin_path(&self);
+    fn close_path(&self);
+    fn move_to(&self, x: f64, y: f64);
+    fn line_to(&self, x: f64, y: f64);
+    fn arc(&self, x: f64, y: f64, radius: f64, start: f64, end: f64) -> Result<(), JsValue>;
+    fn stroke(&self);
+    fn fill(&self);
+    fn set_stroke_style(&self, color: &str);
+    fn set_fill_style(&self, color: &str);
+    fn set_global_alpha(&self, alpha: f64);
+    fn set_line_dash(&self, segments: &[f64]) -> Result<(), JsValue>;
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn clip(&self);
+    fn set_font(&self, font: &str);
+    fn fill_text(&self, text: &str, x: f64, y: f64) -> Result<(), JsValue>;
+    /// Encodes what has been drawn so far as a PNG data URL. Fails if the
+    /// canvas is tainted or gone.
+    fn to_data_url(&self) -> Result<String, JsValue>;
+}
+
+#[cfg(feature = "web")]
+impl Surface for CanvasRenderingContext2d {
+    fn save(&self) {
+        self.save();
+    }
+
+    fn restore(&self) {
+        self.restore();
+    }
+
+    fn translate(&self, x: f64, y: f64) -> Result<(), JsValue> {
+        self.translate(x, y)
+    }
+
+    fn rotate(&self, angle: f64) -> Result<(), JsValue> {
+        self.rotate(angle)
+    }
+
+    fn scale(&self, x: f64, y: f64) -> Result<(), JsValue> {
+        self.scale(x, y)
+    }
+
+    fn begin_path(&self) {
+        self.begin_path();
+    }
+
+    fn close_path(&self) {
+        self.close_path();
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        self.move_to(x, y);
+    }
+
+    fn line_to(&self, x: f64, y: f64) {
+        self.line_to(x, y);
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start: f64, end: f64) -> Result<(), JsValue> {
+        self.arc(x, y, radius, start, end)
+    }
+
+    fn stroke(&self) {
+        self.stroke();
+    }
+
+    fn fill(&self) {
+        self.fill();
+    }
+
+    fn set_stroke_style(&self, color: &str) {
+        self.set_stroke_style_str(color);
+    }
+
+    fn set_fill_style(&self, color: &str) {
+        self.set_fill_style_str(color);
+    }
+
+    fn set_global_alpha(&self, alpha: f64) {
+        self.set_global_alpha(alpha);
+    }
+
+    fn set_line_dash(&self, segments: &[f64]) -> Result<(), JsValue> {
+        let array = js_sys::Array::new();
+        for &segment in segments {
+            array.push(&segment.into());
+        }
+        self.set_line_dash(&array)
+    }
+
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.clear_rect(x, y, width, height);
+    }
+
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.rect(x, y, width, height);
+    }
+
+    fn clip(&self) {
+        self.clip();
+    }
+
+    fn set_font(&self, font: &str) {
+        self.set_font(font);
+    }
+
+    fn fill_text(&self, text: &str, x: f64, y: f64) -> Result<(), JsValue> {
+        self.fill_text(text, x, y)
+    }
+
+    fn to_data_url(&self) -> Result<String, JsValue> {
+        self.canvas()
+            .ok_or_else(|| JsValue::from_str("context has no canvas"))?
+            .to_data_url()
+    }
+}
+
+/// A surface that records every call so tests can assert on what was drawn.
+#[cfg(all(test, feature = "web"))]
+#[derive(Default)]
+pub struct Recorder {
+    pub calls: std::cell::RefCell<Vec<String>>,
+    /// Makes every fallible call fail, as a lost canvas context would.
+    pub lost: bool,
+}
+
+#[cfg(all(test, feature = "web"))]
+impl Recorder {
+    fn record(&self, call: String) {
+        self.calls.borrow_mut().push(call);
+    }
+
+    fn result(&self) -> Result<(), JsValue> {
+        if self.lost {
+            Err(JsValue::NULL)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn contains(&self, call: &str) -> bool {
+        self.calls.borrow().iter().any(|c| c == call)
+    }
+}
+
+#[cfg(all(test, feature = "web"))]
+impl Surface for Recorder {
+    fn save(&self) {
+        self.record("save".into());
+    }
+
+    fn restore(&self) {
+        self.record("restore".into());
+    }
+
+    fn translate(&self, x: f64, y: f64) -> Result<(), JsValue> {
+        self.record(format!("translate({x}, {y})"));
+        self.result()
+    }
+
+    fn rotate(&self, angle: f64) -> Result<(), JsValue> {
+        self.record(format!("rotate({angle})"));
+        self.result()
+    }
+
+    fn scale(&self, x: f64, y: f64) -> Result<(), JsValue> {
+        self.record(format!("scale({x}, {y})"));
+        self.result()
+    }
+
+    fn begin_path(&self) {
+        self.record("begin_path".into());
+    }
+
+    fn close_path(&self) {
+        self.record("close_path".into());
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        self.record(format!("move_to({x}, {y})"));
+    }
+
+    fn line_to(&self, x: f64, y: f64) {
+        self.record(format!("line_to({x}, {y})"));
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start: f64, end: f64) -> Result<(), JsValue> {
+        self.record(format!("arc({x}, {y}, {radius}, {start}, {end})"));
+        self.result()
+    }
+
+    fn stroke(&self) {
+        self.record("stroke".into());
+    }
+
+    fn fill(&self) {
+        self.record("fill".into());
+    }
+
+    fn set_stroke_style(&self, color: &str) {
+        self.record(format!("stroke_style({color})"));
+    }
+
+    fn set_fill_style(&self, color: &str) {
+        self.record(format!("fill_style({color})"));
+    }
+
+    fn set_global_alpha(&self, alpha: f64) {
+        self.record(format!("global_alpha({alpha})"));
+    }
+
+    fn set_line_dash(&self, segments: &[f64]) -> Result<(), JsValue> {
+        self.record(format!("line_dash({segments:?})"));
+        self.result()
+    }
+
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(format!("clear_rect({x}, {y}, {width}, {height})"));
+    }
+
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(format!("rect({x}, {y}, {width}, {height})"));
+    }
+
+    fn clip(&self) {
+        self.record("clip".into());
+    }
+
+    fn set_font(&self, font: &str) {
+        self.record(format!("font({font})"));
+    }
+
+    fn fill_text(&self, text: &str, x: f64, y: f64) -> Result<(), JsValue> {
+        self.record(format!("fill_text({text}, {x}, {y})"));
+        self.result()
+    }
+
+    fn to_data_url(&self) -> Result<String, JsValue> {
+        self.record("to_data_url".into());
+        self.result().map(|()| "data:image/png;base64,".to_string())
+    }
+}
@@ -0,0 +1,105 @@
+//! Drives a `Game` from `requestAnimationFrame` so simple embeds need no
+//! JavaScript game loop of their own.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::Game;
+
+/// Longest step a single frame may take, in seconds. Anything longer, such
+/// as returning to a background tab, is treated as a hitch rather than
+/// simulated in one huge jump.
+pub const MAX_FRAME_DELTA: f64 = 0.25;
+
+/// Seconds between two animation frame timestamps given in milliseconds.
+/// The first frame, with no previous timestamp, advances by nothing.
+pub fn frame_delta(previous_ms: Option<f64>, now_ms: f64) -> f64 {
+    match previous_ms {
+        Some(previous) => ((now_ms - previous) / 1000.0).clamp(0.0, MAX_FRAME_DELTA),
+        None => 0.0,
+    }
+}
+
+/// The per-frame callback, kept in a shared slot so it can reschedule
+/// itself.
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+
+/// Handle to a running frame loop. Dropping it leaves the loop running;
+/// call `stop` to end it and free the game.
+#[wasm_bindgen]
+pub struct FrameLoop {
+    running: Rc<Cell<bool>>,
+    request: Rc<Cell<Option<i32>>>,
+    callback: FrameCallback,
+}
+
+#[wasm_bindgen]
+impl FrameLoop {
+    /// Stops ticking the game after the current frame and drops the
+    /// callback, along with the game it owns.
+    pub fn stop(&self) {
+        self.running.set(false);
+        if let (Some(window), Some(id)) = (web_sys::window(), self.request.take()) {
+            let _ = window.cancel_animation_frame(id);
+        }
+        self.callback.borrow_mut().take();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.get()
+    }
+}
+
+/// Ticks `game` on every animation frame until the returned handle is
+/// stopped. Takes ownership of the game; keep talking to it through the
+/// observer and events it was set up with.
+#[wasm_bindgen]
+pub fn run(game: Game) -> Result<FrameLoop, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let running = Rc::new(Cell::new(true));
+    let request = Rc::new(Cell::new(None));
+    let game = RefCell::new(game);
+    let previous = Cell::new(None);
+
+    // The callback reschedules itself, so it holds a reference to its own
+    // closure. `stop` cancels the pending frame and empties the slot, which
+    // breaks the cycle.
+    let callback: FrameCallback = Rc::new(RefCell::new(None));
+    let own_callback = callback.clone();
+    let (loop_running, loop_request) = (running.clone(), request.clone());
+    let loop_window = window.clone();
+    *callback.borrow_mut() = Some(Closure::new(move |now: f64| {
+        if !loop_running.get() {
+            own_callback.borrow_mut().take();
+            return;
+        }
+        let dt = frame_delta(previous.replace(Some(now)), now);
+        if let Err(error) = game.borrow_mut().tick(dt) {
+            web_sys::console::error_1(&error);
+        }
+        if let Some(next) = own_callback.borrow().as_ref() {
+            let id = loop_window.request_animation_frame(next.as_ref().unchecked_ref());
+            loop_request.set(id.ok());
+        }
+    }));
+
+    if let Some(first) = callback.borrow().as_ref() {
+        request.set(Some(window.request_animation_frame(first.as_ref().unchecked_ref())?));
+    }
+    Ok(FrameLoop { running, request, callback })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_delta() {
+        assert_eq!(frame_delta(None, 1000.0), 0.0);
+        assert!((frame_delta(Some(1000.0), 1016.0) - 0.016).abs() < 1e-12);
+        assert_eq!(frame_delta(Some(1000.0), 5000.0), MAX_FRAME_DELTA);
+        assert_eq!(frame_delta(Some(1000.0), 900.0), 0.0);
+    }
+}
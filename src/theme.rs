@@ -0,0 +1,218 @@
+/// A color with straight alpha, for values the game blends itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: f64,
+}
+
+impl Rgba {
+    pub const fn new(r: u8, g: u8, b: u8, a: f64) -> Rgba {
+        Rgba { r, g, b, a }
+    }
+
+    /// An opaque color from a `0xRRGGBB` value.
+    pub const fn from_hex(hex: u32) -> Rgba {
+        Rgba::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 1.0)
+    }
+
+    pub fn css(&self) -> String {
+        format!("rgba({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+    }
+
+    fn lerp(self, other: Rgba, t: f64) -> Rgba {
+        let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgba {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: self.a + (other.a - self.a) * t,
+        }
+    }
+}
+
+/// Samples evenly spaced color stops at `t` in `0..=1`.
+pub fn gradient_at(stops: &[Rgba], t: f64) -> Rgba {
+    match stops {
+        [] => Rgba::new(255, 255, 255, 1.0),
+        [only] => *only,
+        _ => {
+            let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+            let index = (scaled.floor() as usize).min(stops.len() - 2);
+            stops[index].lerp(stops[index + 1], scaled - index as f64)
+        }
+    }
+}
+
+/// Exhaust fades from white-hot through orange to nothing.
+const THRUST_GRADIENT: [Rgba; 3] = [
+    Rgba::new(255, 255, 255, 1.0),
+    Rgba::new(255, 140, 0, 0.8),
+    Rgba::new(255, 60, 0, 0.0),
+];
+
+/// Waves it takes to move from one background stop to the next.
+pub const WAVES_PER_BACKGROUND: u32 = 3;
+
+/// Colors used when drawing each kind of entity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub ship: String,
+    pub asteroid: String,
+    /// Kept distinct from the generic white so shots read apart from debris.
+    pub bullet: String,
+    /// Flying saucers and their shots.
+    pub ufo: String,
+    /// Colors a thrust particle passes through over its lifetime.
+    pub thrust_gradient: Vec<Rgba>,
+    /// Fill behind everything this wave. `None` leaves the canvas clear so
+    /// the page shows through.
+    pub background: Option<Rgba>,
+    /// Backgrounds the waves blend through, one stop every
+    /// `WAVES_PER_BACKGROUND` waves and holding on the last. Empty keeps
+    /// the background fixed.
+    pub wave_backgrounds: Vec<Rgba>,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Palette::Classic.theme()
+    }
+}
+
+impl Theme {
+    /// The background for `wave`, counting from 1, blended from
+    /// `wave_backgrounds`, or the fixed background if there are none.
+    pub fn background_for_wave(&self, wave: u32) -> Option<Rgba> {
+        match self.wave_backgrounds.len() {
+            0 => self.background,
+            stops => {
+                let span = (WAVES_PER_BACKGROUND * (stops as u32 - 1)).max(1);
+                let t = wave.saturating_sub(1) as f64 / span as f64;
+                Some(gradient_at(&self.wave_backgrounds, t))
+            }
+        }
+    }
+
+    /// Replaces every color with the named preset. Unknown names reset the
+    /// theme to the classic palette and return `false`.
+    pub fn apply_palette(&mut self, name: &str) -> bool {
+        match Palette::from_name(name) {
+            Some(palette) => {
+                *self = palette.theme();
+                true
+            }
+            None => {
+                *self = Palette::Classic.theme();
+                false
+            }
+        }
+    }
+}
+
+/// Puts `color` in `slot`, trimmed, unless it is blank. Returns whether the
+/// color was taken.
+pub fn replace_color(slot: &mut String, color: &str) -> bool {
+    let color = color.trim();
+    if color.is_empty() {
+        return false;
+    }
+    *slot = color.to_string();
+    true
+}
+
+/// Named color presets. The colorblind-safe sets are drawn from the
+/// Okabe-Ito palette so the ship, rocks and shots stay distinguishable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Palette {
+    Classic,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+    HighContrast,
+}
+
+impl Palette {
+    pub fn from_name(name: &str) -> Option<Palette> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "classic" | "default" => Some(Palette::Classic),
+            "deuteranopia" => Some(Palette::Deuteranopia),
+            "protanopia" => Some(Palette::Protanopia),
+            "tritanopia" => Some(Palette::Tritanopia),
+            "high_contrast" | "high-contrast" => Some(Palette::HighContrast),
+            _ => None,
+        }
+    }
+
+    pub fn theme(self) -> Theme {
+        let (ship, asteroid, bullet, ufo) = match self {
+            Palette::Classic => ("white", "white", "cyan", "#FF6060"),
+            Palette::Deuteranopia => ("#56B4E9", "#E69F00", "#F0E442", "#CC79A7"),
+            Palette::Protanopia => ("#56B4E9", "#F0E442", "#CC79A7", "#E69F00"),
+            Palette::Tritanopia => ("#CC79A7", "#009E73", "#D55E00", "#56B4E9"),
+            Palette::HighContrast => ("#FFFFFF", "#FFFF00", "#00FFFF", "#FF00FF"),
+        };
+        Theme {
+            ship: ship.to_string(),
+            asteroid: asteroid.to_string(),
+            bullet: bullet.to_string(),
+            ufo: ufo.to_string(),
+            thrust_gradient: THRUST_GRADIENT.to_vec(),
+            background: None,
+            wave_backgrounds: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_selection() {
+        let mut theme = Theme::default();
+        assert!(theme.apply_palette("deuteranopia"));
+        assert_eq!(theme, Palette::Deuteranopia.theme());
+        assert_eq!(theme.ship, "#56B4E9");
+        assert_eq!(theme.asteroid, "#E69F00");
+        assert_eq!(theme.bullet, "#F0E442");
+
+        assert!(!theme.apply_palette("not-a-palette"));
+        assert_eq!(theme, Palette::Classic.theme());
+    }
+
+    #[test]
+    fn test_replace_color() {
+        let mut theme = Theme::default();
+        assert!(replace_color(&mut theme.ship, " #ff8800 "));
+        assert_eq!(theme.ship, "#ff8800");
+        assert!(!replace_color(&mut theme.asteroid, "  "));
+        assert_eq!(theme.asteroid, "white");
+    }
+
+    #[test]
+    fn test_wave_backgrounds() {
+        let mut theme = Theme::default();
+        assert_eq!(theme.background_for_wave(1), None);
+        assert_eq!(theme.background_for_wave(9), None);
+
+        theme.wave_backgrounds = vec![Rgba::from_hex(0x000000), Rgba::from_hex(0x3000c0)];
+        let first = theme.background_for_wave(1);
+        assert_eq!(first, Some(Rgba::new(0, 0, 0, 1.0)));
+        assert_ne!(theme.background_for_wave(2), first);
+        assert_eq!(
+            theme.background_for_wave(1 + WAVES_PER_BACKGROUND),
+            Some(Rgba::new(0x30, 0x00, 0xc0, 1.0))
+        );
+        assert_eq!(theme.background_for_wave(50), theme.background_for_wave(4));
+    }
+
+    #[test]
+    fn test_gradient_sampling() {
+        let stops = [Rgba::new(0, 0, 0, 1.0), Rgba::new(200, 100, 50, 0.0)];
+        assert_eq!(gradient_at(&stops, 0.5), Rgba::new(100, 50, 25, 0.5));
+        assert_eq!(gradient_at(&stops, 2.0), stops[1]);
+        assert_eq!(Rgba::new(1, 2, 3, 0.5).css(), "rgba(1, 2, 3, 0.5)");
+    }
+}
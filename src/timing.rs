@@ -0,0 +1,119 @@
+//! Optional profiling of how long each frame spends updating versus
+//! rendering, kept over a rolling window of recent frames.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+/// Frames kept in the rolling window.
+pub const TIMING_WINDOW: usize = 120;
+
+/// Upper edges of the histogram buckets, in milliseconds. Anything slower
+/// than the last edge lands in one extra overflow bucket.
+pub const BUCKET_BOUNDS_MS: [f64; 6] = [1.0, 2.0, 4.0, 8.0, 16.0, 33.0];
+
+/// A monotonic clock in milliseconds: `performance.now()` in the browser
+/// and `Instant` everywhere else.
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map(|performance| performance.now())
+        .unwrap_or(0.0)
+}
+
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+pub fn now_ms() -> f64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+/// Recent update and render durations.
+#[derive(Clone, Debug, Default)]
+pub struct FrameTimings {
+    update: VecDeque<f64>,
+    render: VecDeque<f64>,
+}
+
+impl FrameTimings {
+    pub fn record(&mut self, update_ms: f64, render_ms: f64) {
+        for (samples, value) in [(&mut self.update, update_ms), (&mut self.render, render_ms)] {
+            if samples.len() == TIMING_WINDOW {
+                samples.pop_front();
+            }
+            samples.push_back(value);
+        }
+    }
+
+    pub fn report(&self) -> TimingReport {
+        TimingReport {
+            bucket_bounds_ms: BUCKET_BOUNDS_MS.to_vec(),
+            update: PhaseReport::from_samples(&self.update),
+            render: PhaseReport::from_samples(&self.render),
+        }
+    }
+}
+
+/// What the host gets back from `timing_report`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TimingReport {
+    pub bucket_bounds_ms: Vec<f64>,
+    pub update: PhaseReport,
+    pub render: PhaseReport,
+}
+
+/// Summary of one phase over the window. `buckets[i]` counts frames at or
+/// under `bucket_bounds_ms[i]`, and the final bucket counts the rest.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PhaseReport {
+    pub samples: usize,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    pub buckets: Vec<u32>,
+}
+
+impl PhaseReport {
+    fn from_samples(samples: &VecDeque<f64>) -> PhaseReport {
+        let mut buckets = vec![0; BUCKET_BOUNDS_MS.len() + 1];
+        for &sample in samples {
+            let index = BUCKET_BOUNDS_MS
+                .iter()
+                .position(|&edge| sample <= edge)
+                .unwrap_or(BUCKET_BOUNDS_MS.len());
+            buckets[index] += 1;
+        }
+        let total: f64 = samples.iter().sum();
+        PhaseReport {
+            samples: samples.len(),
+            mean_ms: if samples.is_empty() { 0.0 } else { total / samples.len() as f64 },
+            max_ms: samples.iter().copied().fold(0.0, f64::max),
+            buckets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_buckets() {
+        let mut timings = FrameTimings::default();
+        timings.record(0.5, 3.0);
+        timings.record(1.5, 50.0);
+        let report = timings.report();
+        assert_eq!(report.update.samples, 2);
+        assert_eq!(report.update.buckets, vec![1, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(report.render.buckets, vec![0, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(report.render.max_ms, 50.0);
+        assert_eq!(report.update.mean_ms, 1.0);
+
+        for _ in 0..TIMING_WINDOW {
+            timings.record(0.1, 0.1);
+        }
+        assert_eq!(timings.report().update.samples, TIMING_WINDOW);
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+/// Radius of each asteroid size class, in pixels.
+pub const LARGE_ASTEROID_SIZE: f64 = 40.0;
+pub const MEDIUM_ASTEROID_SIZE: f64 = 20.0;
+pub const SMALL_ASTEROID_SIZE: f64 = 10.0;
+
+/// What a single wave is made of.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaveSpec {
+    pub large: u32,
+    pub medium: u32,
+    pub small: u32,
+    /// Enter from the screen edges heading inward, instead of appearing
+    /// anywhere in the field.
+    pub from_edges: bool,
+}
+
+impl WaveSpec {
+    /// The opening field of five medium rocks.
+    pub fn classic() -> WaveSpec {
+        WaveSpec {
+            medium: 5,
+            ..WaveSpec::default()
+        }
+    }
+
+    /// `(size, count)` for every size class in the wave.
+    pub fn asteroids(&self) -> [(f64, u32); 3] {
+        [
+            (LARGE_ASTEROID_SIZE, self.large),
+            (MEDIUM_ASTEROID_SIZE, self.medium),
+            (SMALL_ASTEROID_SIZE, self.small),
+        ]
+    }
+}
+
+/// A wave cleared at or after this many seconds earns no time bonus.
+pub const WAVE_PAR_SECONDS: f64 = 60.0;
+
+/// Largest time and accuracy bonuses a single wave can award.
+pub const MAX_TIME_BONUS: u32 = 1000;
+pub const MAX_ACCURACY_BONUS: u32 = 1000;
+
+/// Range the adaptive difficulty factor is held to.
+pub const MIN_DIFFICULTY: f64 = 0.5;
+pub const MAX_DIFFICULTY: f64 = 2.0;
+
+/// Difficulty gained by a perfectly accurate wave, and lost by one where
+/// every shot missed.
+pub const ACCURACY_SWING: f64 = 0.2;
+
+/// Difficulty gained for clearing a wave without dying.
+pub const SURVIVAL_STEP: f64 = 0.1;
+
+/// Difficulty lost for each life lost during a wave.
+pub const DEATH_STEP: f64 = 0.2;
+
+/// Counters for the wave in progress, reset whenever a wave is cleared.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WaveStats {
+    /// Seconds of play since the wave began.
+    pub elapsed: f64,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub deaths: u32,
+}
+
+impl WaveStats {
+    /// The share of shots fired that hit, or 0 before the first shot.
+    pub fn accuracy(&self) -> f64 {
+        if self.shots_fired == 0 {
+            0.0
+        } else {
+            self.shots_hit.min(self.shots_fired) as f64 / self.shots_fired as f64
+        }
+    }
+
+    /// Adds another wave's counters to these, for totals across a run.
+    pub fn accumulate(&mut self, other: &WaveStats) {
+        self.elapsed += other.elapsed;
+        self.shots_fired += other.shots_fired;
+        self.shots_hit += other.shots_hit;
+        self.deaths += other.deaths;
+    }
+
+    /// The `(time, accuracy)` bonus for clearing the wave with these stats.
+    /// Time pays out linearly down to 0 at `WAVE_PAR_SECONDS`; accuracy pays
+    /// in proportion to the share of shots that hit.
+    pub fn clear_bonus(&self) -> (u32, u32) {
+        let remaining = (WAVE_PAR_SECONDS - self.elapsed).max(0.0);
+        let time = MAX_TIME_BONUS as f64 * remaining / WAVE_PAR_SECONDS;
+        let accuracy = MAX_ACCURACY_BONUS as f64 * self.accuracy();
+        (time as u32, accuracy as u32)
+    }
+
+    /// How far to move the adaptive difficulty factor after a wave played
+    /// like this. Accurate shooting and surviving push it up; every death
+    /// pulls it down.
+    pub fn difficulty_adjustment(&self) -> f64 {
+        let accuracy = if self.shots_fired == 0 {
+            0.0
+        } else {
+            (self.accuracy() - 0.5) * 2.0 * ACCURACY_SWING
+        };
+        let survival = if self.deaths == 0 { SURVIVAL_STEP } else { 0.0 };
+        accuracy + survival - self.deaths as f64 * DEATH_STEP
+    }
+}
+
+/// The spec for the zero-based `wave`. Past the end of `waves` the last spec
+/// repeats with one more large asteroid for every wave beyond it.
+pub fn spec_for(waves: &[WaveSpec], wave: usize) -> WaveSpec {
+    let Some(last) = waves.last() else {
+        return WaveSpec::classic();
+    };
+    match waves.get(wave) {
+        Some(spec) => *spec,
+        None => WaveSpec {
+            large: last.large + (wave + 1 - waves.len()) as u32,
+            ..*last
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_extrapolation() {
+        let waves = [
+            WaveSpec { large: 1, ..WaveSpec::default() },
+            WaveSpec { large: 2, medium: 1, small: 3, from_edges: true },
+        ];
+        assert_eq!(spec_for(&waves, 0), waves[0]);
+        assert_eq!(spec_for(&waves, 1), waves[1]);
+        assert_eq!(
+            spec_for(&waves, 3),
+            WaveSpec { large: 4, medium: 1, small: 3, from_edges: true }
+        );
+        assert_eq!(spec_for(&[], 7), WaveSpec::classic());
+    }
+
+    #[test]
+    fn test_clear_bonus() {
+        let fast = WaveStats { elapsed: 10.0, shots_fired: 10, shots_hit: 9, deaths: 0 };
+        let slow = WaveStats { elapsed: 50.0, shots_fired: 40, shots_hit: 10, deaths: 0 };
+        let (fast_time, fast_accuracy) = fast.clear_bonus();
+        let (slow_time, slow_accuracy) = slow.clear_bonus();
+        assert!(fast_time > slow_time);
+        assert!(fast_accuracy > slow_accuracy);
+        assert_eq!(fast_accuracy, 900);
+
+        let idle = WaveStats { elapsed: 170.0, ..WaveStats::default() };
+        assert_eq!(idle.clear_bonus(), (0, 0));
+    }
+}